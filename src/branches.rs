@@ -0,0 +1,188 @@
+use crate::conversation::{ConversationContext, Message};
+use serde::{Deserialize, Serialize};
+
+/// One branch of a conversation tree. A branch shares the first `fork_at`
+/// messages of its parent and stores only what comes after. The active
+/// branch lives in `ConversationContext::input`, its `messages` are only
+/// brought up to date when another branch is checked out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Branch {
+    pub parent: Option<usize>,
+    pub fork_at: usize,
+    pub label: String,
+    pub messages: Vec<Message>,
+}
+
+impl ConversationContext {
+    /// Starts a new branch from the first `at` messages of the active one.
+    /// The old continuation stays reachable through `checkout`.
+    pub fn fork(&mut self, at: usize, label: &str) -> usize {
+        self.store_active_branch();
+        self.branches.push(Branch {
+            parent: Some(self.active_branch),
+            fork_at: at,
+            label: label.into(),
+            messages: Vec::new(),
+        });
+        self.active_branch = self.branches.len() - 1;
+        self.input.truncate(at);
+        self.active_branch
+    }
+
+    pub fn checkout(&mut self, id: usize) -> Result<(), String> {
+        if id >= self.branches.len() {
+            return Err(format!("No branch {}. Use :branches to list them.", id));
+        }
+        self.store_active_branch();
+        self.input = self.branch_messages(id);
+        self.active_branch = id;
+        Ok(())
+    }
+
+    /// Drops the branch the last `fork` made and checks out `previous`
+    /// again, for an edit that was never answered.
+    pub fn abandon_fork(&mut self, previous: usize) -> Result<(), String> {
+        self.checkout(previous)?;
+        self.branches.pop();
+        Ok(())
+    }
+
+    /// Forgets every branch except the live messages.
+    pub fn reset_branches(&mut self) {
+        self.branches.clear();
+        self.active_branch = 0;
+    }
+
+    /// The full message list of branch `id`, from the root down.
+    pub fn branch_messages(&self, id: usize) -> Vec<Message> {
+        if id == self.active_branch {
+            return self.input.clone();
+        }
+        let branch = &self.branches[id];
+        let mut messages = self.shared_prefix(branch);
+        messages.extend(branch.messages.iter().cloned());
+        messages
+    }
+
+    fn shared_prefix(&self, branch: &Branch) -> Vec<Message> {
+        match branch.parent {
+            Some(parent) => {
+                let mut prefix = self.branch_messages(parent);
+                prefix.truncate(branch.fork_at);
+                prefix
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn store_active_branch(&mut self) {
+        if self.branches.is_empty() {
+            self.branches.push(Branch {
+                parent: None,
+                fork_at: 0,
+                label: "main".into(),
+                messages: Vec::new(),
+            });
+            self.active_branch = 0;
+        }
+
+        let id = self.active_branch;
+        let prefix = self.shared_prefix(&self.branches[id]);
+        let mut stored = prefix.clone();
+        stored.extend(self.branches[id].messages.iter().cloned());
+
+        // Children share what this branch held when they forked. Where that
+        // has been rewritten since, they keep a copy of the old prefix.
+        for child in self.branches.iter_mut().filter(|b| b.parent == Some(id)) {
+            let old_prefix = &stored[..child.fork_at.min(stored.len())];
+            if child.fork_at > 0 && !self.input.starts_with(old_prefix) {
+                let mut messages = old_prefix.to_vec();
+                messages.append(&mut child.messages);
+                child.messages = messages;
+                child.fork_at = 0;
+            }
+        }
+
+        let shares_prefix = self.input.starts_with(&prefix);
+        let branch = &mut self.branches[id];
+        if shares_prefix {
+            branch.messages = self.input[prefix.len()..].to_vec();
+        } else {
+            // History before the fork was rewritten on this branch (cleared,
+            // summarized, trimmed), so it keeps a copy of its own.
+            branch.fork_at = 0;
+            branch.messages = self.input.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(messages: &[Message]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fork_and_checkout() -> Result<(), String> {
+        let mut ctx = ConversationContext::new("gpt-4o", false);
+        for text in ["dev", "q1", "a1", "q2", "a2"] {
+            ctx.input.push(Message::new("user", text));
+        }
+
+        let edited = ctx.fork(3, "q2 edited");
+        ctx.input.push(Message::new("user", "q2'"));
+        ctx.input.push(Message::new("assistant", "a2'"));
+        assert_eq!(edited, 1);
+
+        ctx.checkout(0)?;
+        assert_eq!(contents(&ctx.input), ["dev", "q1", "a1", "q2", "a2"]);
+
+        // Growing the parent does not change what the child shares with it.
+        ctx.input.push(Message::new("user", "q3"));
+        ctx.checkout(1)?;
+        assert_eq!(contents(&ctx.input), ["dev", "q1", "a1", "q2'", "a2'"]);
+        assert_eq!(
+            contents(&ctx.branch_messages(0)),
+            ["dev", "q1", "a1", "q2", "a2", "q3"]
+        );
+
+        assert!(ctx.checkout(7).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewriting_the_parent_keeps_the_child_prefix() -> Result<(), String> {
+        let mut ctx = ConversationContext::new("gpt-4o", false);
+        for text in ["dev", "q1", "a1", "q2", "a2"] {
+            ctx.input.push(Message::new("user", text));
+        }
+        ctx.fork(3, "q2 edited");
+        ctx.input.push(Message::new("user", "q2'"));
+
+        // Trimmed on the parent, as `:ctx drop 1..2` or summarizing would.
+        ctx.checkout(0)?;
+        ctx.input.drain(1..3);
+        ctx.checkout(1)?;
+        assert_eq!(contents(&ctx.input), ["dev", "q1", "a1", "q2'"]);
+        assert_eq!(contents(&ctx.branch_messages(0)), ["dev", "q2", "a2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_abandon_fork() -> Result<(), String> {
+        let mut ctx = ConversationContext::new("gpt-4o", false);
+        for text in ["dev", "q1", "a1"] {
+            ctx.input.push(Message::new("user", text));
+        }
+        ctx.fork(1, "q1 edited");
+        ctx.input.push(Message::new("user", "q1'"));
+
+        ctx.abandon_fork(0)?;
+        assert_eq!(contents(&ctx.input), ["dev", "q1", "a1"]);
+        assert_eq!(ctx.branches.len(), 1);
+        assert_eq!(ctx.active_branch, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,445 @@
+use crate::conversation::ConversationContext;
+use crate::storage::{ConversationFile, is_plain_name, read_context};
+use crate::utils::format_timestamp;
+use crossterm::{
+    QueueableCommand, cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::{Print, Stylize},
+    terminal::{self, ClearType},
+};
+use std::error::Error;
+use std::fs;
+use std::io::{Stdout, Write, stdout};
+use std::path::{Path, PathBuf};
+
+const PREVIEW_MESSAGES: usize = 6;
+const FOOTER_KEYS: &str =
+    "↑↓ move  Enter load  Ctrl-R rename  Ctrl-D duplicate  Ctrl-X delete  Esc cancel";
+
+/// The conversation picked in the browser and its name, if any.
+type Chosen = Option<(String, ConversationContext)>;
+
+/// A saved conversation as listed in the browser.
+struct Entry {
+    path: PathBuf,
+    name: String,
+    file: ConversationFile,
+}
+
+/// What the footer line is being used for.
+enum Mode {
+    Browse,
+    Rename(String),
+    ConfirmDelete,
+}
+
+struct Browser {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    skipped: usize,
+    filter: String,
+    visible: Vec<usize>,
+    selected: usize,
+    mode: Mode,
+    status: String,
+}
+
+/// Restores the terminal however the browser exits.
+struct RawScreen;
+
+impl RawScreen {
+    fn enter() -> Result<Self, Box<dyn Error>> {
+        terminal::enable_raw_mode()?;
+        stdout()
+            .queue(terminal::EnterAlternateScreen)?
+            .queue(cursor::Hide)?
+            .flush()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        let _ = stdout()
+            .queue(cursor::Show)
+            .and_then(|out| out.queue(terminal::LeaveAlternateScreen))
+            .and_then(|out| out.flush());
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lets the user pick a conversation saved in `dir`, with fuzzy filtering,
+/// a preview and rename, duplicate and delete. Returns the chosen one.
+pub(crate) fn browse_conversations(dir: &Path) -> Result<Chosen, Box<dyn Error>> {
+    let mut browser = Browser::load(dir)?;
+    if browser.entries.is_empty() {
+        eprintln!(
+            "\nNo saved conversations in {}. Save one with :sc.\n",
+            dir.display()
+        );
+        return Ok(None);
+    }
+
+    let _screen = RawScreen::enter()?;
+    let mut out = stdout();
+    loop {
+        browser.draw(&mut out)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(chosen) = browser.handle_key(key)? {
+            return Ok(chosen);
+        }
+    }
+}
+
+impl Browser {
+    fn load(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut browser = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            skipped: 0,
+            filter: String::new(),
+            visible: Vec::new(),
+            selected: 0,
+            mode: Mode::Browse,
+            status: String::new(),
+        };
+        browser.reload()?;
+        Ok(browser)
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        self.entries.clear();
+        self.skipped = 0;
+        if self.dir.exists() {
+            for path in fs::read_dir(&self.dir)?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            {
+                match ConversationFile::read(&path) {
+                    Ok(file) => self.entries.push(Entry {
+                        name: file_stem(&path),
+                        path,
+                        file,
+                    }),
+                    Err(_) => self.skipped += 1,
+                }
+            }
+        }
+        self.entries
+            .sort_by_key(|e| std::cmp::Reverse(e.file.meta.updated));
+        self.apply_filter();
+        Ok(())
+    }
+
+    fn apply_filter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| {
+                let haystack = format!(
+                    "{} {} {}",
+                    e.name,
+                    e.file.meta.title.as_deref().unwrap_or(""),
+                    e.file.model
+                );
+                fuzzy_score(&self.filter, &haystack).map(|score| (score, i))
+            })
+            .collect();
+        // Stable, so equal scores keep the most recent first.
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.visible = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+    }
+
+    fn current(&self) -> Option<&Entry> {
+        self.visible
+            .get(self.selected)
+            .and_then(|&i| self.entries.get(i))
+    }
+
+    /// Returns `Some` once the browser should close, with the chosen
+    /// conversation if there is one.
+    fn handle_key(&mut self, key: KeyEvent) -> Result<Option<Chosen>, Box<dyn Error>> {
+        self.status.clear();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match &mut self.mode {
+            Mode::Rename(name) => match key.code {
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    self.mode = Mode::Browse;
+                    self.rename(&name)?;
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) if !ctrl => name.push(c),
+                _ => {}
+            },
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Browse;
+                if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    self.delete()?;
+                } else {
+                    self.status = "Delete cancelled.".into();
+                }
+            }
+            Mode::Browse => match key.code {
+                KeyCode::Esc => return Ok(Some(None)),
+                KeyCode::Char('c') if ctrl => return Ok(Some(None)),
+                KeyCode::Enter => {
+                    if let Some(entry) = self.current() {
+                        let context = read_context(&entry.path)?;
+                        return Ok(Some(Some((entry.name.clone(), context))));
+                    }
+                }
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down if self.selected + 1 < self.visible.len() => self.selected += 1,
+                KeyCode::Char('r') if ctrl => {
+                    if let Some(entry) = self.current() {
+                        self.mode = Mode::Rename(entry.name.clone());
+                    }
+                }
+                KeyCode::Char('d') if ctrl => self.duplicate()?,
+                KeyCode::Char('x') if ctrl && self.current().is_some() => {
+                    self.mode = Mode::ConfirmDelete;
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.apply_filter();
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    self.filter.push(c);
+                    self.selected = 0;
+                    self.apply_filter();
+                }
+                _ => {}
+            },
+        }
+        Ok(None)
+    }
+
+    fn rename(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let Some(entry) = self.current() else {
+            return Ok(());
+        };
+        if name.is_empty() || name == entry.name {
+            return Ok(());
+        }
+        if !is_plain_name(name) {
+            self.status = "Names cannot contain '/', '\\' or '..'.".to_string();
+            return Ok(());
+        }
+        let target = self.dir.join(format!("{}.json", name));
+        if target.exists() {
+            self.status = format!("{} already exists.", name);
+            return Ok(());
+        }
+
+        let mut file = entry.file.clone();
+        let old_path = entry.path.clone();
+        let old_name = entry.name.clone();
+        file.meta.title = Some(name.to_string());
+        file.write(&target)?;
+        fs::remove_file(&old_path)?;
+        self.status = format!("Renamed {} to {}.", old_name, name);
+        self.reload_selecting(name)
+    }
+
+    fn duplicate(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(entry) = self.current() else {
+            return Ok(());
+        };
+        let mut n = 1;
+        let name = loop {
+            let candidate = if n == 1 {
+                format!("{} copy", entry.name)
+            } else {
+                format!("{} copy {}", entry.name, n)
+            };
+            if !self.dir.join(format!("{}.json", candidate)).exists() {
+                break candidate;
+            }
+            n += 1;
+        };
+
+        let mut file = entry.file.clone();
+        file.meta.title = Some(name.clone());
+        file.write(&self.dir.join(format!("{}.json", name)))?;
+        self.status = format!("Duplicated as {}.", name);
+        self.reload_selecting(&name)
+    }
+
+    fn delete(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(entry) = self.current() else {
+            return Ok(());
+        };
+        fs::remove_file(&entry.path)?;
+        self.status = format!("Deleted {}.", entry.name);
+        self.reload()
+    }
+
+    fn reload_selecting(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.reload()?;
+        if let Some(pos) = self
+            .visible
+            .iter()
+            .position(|&i| self.entries[i].name == name)
+        {
+            self.selected = pos;
+        }
+        Ok(())
+    }
+
+    fn draw(&self, out: &mut Stdout) -> Result<(), Box<dyn Error>> {
+        let (width, height) = terminal::size()?;
+        let width = width as usize;
+        let height = height as usize;
+        let list_rows = (height.saturating_sub(4) / 2).max(3);
+
+        out.queue(terminal::Clear(ClearType::All))?;
+        let mut header = format!(
+            "Conversations ({}/{})  Filter: {}",
+            self.visible.len(),
+            self.entries.len(),
+            self.filter
+        );
+        if self.skipped > 0 {
+            header.push_str(&format!("  ({} unreadable)", self.skipped));
+        }
+        line(out, 0, &fit(&header, width).bold().to_string())?;
+
+        // Keep the selection in view.
+        let first = self.selected.saturating_sub(list_rows.saturating_sub(1));
+        for (row, &i) in self.visible.iter().skip(first).take(list_rows).enumerate() {
+            let entry = &self.entries[i];
+            let text = fit(
+                &format!(
+                    "{:<28} {}  {:>4} msgs  {}",
+                    fit(&entry.name, 28),
+                    format_timestamp(entry.file.meta.updated),
+                    entry.file.messages.len(),
+                    entry.file.model
+                ),
+                width,
+            );
+            let text = if first + row == self.selected {
+                text.reverse().to_string()
+            } else {
+                text
+            };
+            line(out, 2 + row, &text)?;
+        }
+
+        let preview_top = 3 + list_rows;
+        line(out, preview_top, &"─".repeat(width))?;
+        if let Some(entry) = self.current() {
+            if let Some(title) = &entry.file.meta.title
+                && *title != entry.name
+            {
+                line(out, preview_top + 1, &fit(title, width).bold().to_string())?;
+            }
+            let preview_rows = height.saturating_sub(preview_top + 4);
+            for (row, msg) in entry
+                .file
+                .messages
+                .iter()
+                .filter(|m| m.role != "developer")
+                .take(PREVIEW_MESSAGES.min(preview_rows))
+                .enumerate()
+            {
+                let text = format!("{:>9}: {}", msg.role, msg.preview(width));
+                line(out, preview_top + 2 + row, &fit(&text, width))?;
+            }
+        }
+
+        let footer = match &self.mode {
+            Mode::Browse if !self.status.is_empty() => self.status.clone(),
+            Mode::Browse => FOOTER_KEYS.to_string(),
+            Mode::Rename(name) => format!("Rename to: {}█", name),
+            Mode::ConfirmDelete => format!(
+                "Delete {}? (y/n)",
+                self.current().map(|e| e.name.as_str()).unwrap_or("")
+            ),
+        };
+        line(
+            out,
+            height.saturating_sub(1),
+            &fit(&footer, width).dim().to_string(),
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn line(out: &mut Stdout, row: usize, text: &str) -> Result<(), Box<dyn Error>> {
+    out.queue(cursor::MoveTo(0, row as u16))?
+        .queue(Print(text))?;
+    Ok(())
+}
+
+/// Cuts `text` down to `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        text.chars()
+            .take(width.saturating_sub(1))
+            .chain(['…'])
+            .collect()
+    } else {
+        text.to_string()
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Scores `text` against `query` when every query character appears in
+/// order. Consecutive matches and matches at the start of words score
+/// higher. An empty query matches everything equally.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "rust lifetimes"), None);
+        assert!(fuzzy_score("rl", "rust lifetimes").is_some());
+
+        // Word starts and runs beat scattered matches.
+        let prefix = fuzzy_score("life", "rust lifetimes").unwrap_or_default();
+        let scattered = fuzzy_score("life", "a long input file").unwrap_or_default();
+        assert!(prefix > scattered);
+    }
+}
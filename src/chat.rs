@@ -0,0 +1,134 @@
+use crate::chat_client::{anthropic_chat, gemini_chat, gemini_stream, send_request, stream};
+use crate::context_window::fit_to_window;
+use crate::conversation::{AnthropicMessage, ConversationContext, Message, ResponseC, Usage};
+use crate::critic::critique_last_reply;
+use crate::message_printer::{MessageType, print_message, print_reply, print_usage};
+use crate::tc_config::ConfigTC;
+use crate::utils::now_secs;
+use crate::watch::refresh_files;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// Adds `line` as the next user message and gets the model's reply.
+pub(crate) async fn send_user_message(
+    ctx: &mut ConversationContext,
+    line: String,
+    config: &ConfigTC,
+) -> Result<(), Box<dyn Error>> {
+    send_with_attachments(ctx, vec![], line, config).await
+}
+
+/// Like `send_user_message`, with file messages that go in just before
+/// `line`. Neither is kept if the request is not sent.
+pub(crate) async fn send_with_attachments(
+    ctx: &mut ConversationContext,
+    attachments: Vec<Message>,
+    line: String,
+    config: &ConfigTC,
+) -> Result<(), Box<dyn Error>> {
+    ctx.alternates.clear();
+    let before = ctx.input.len();
+    ctx.input.extend(attachments);
+    ctx.input.push(Message {
+        created: Some(now_secs()),
+        ..Message::new("user", line)
+    });
+
+    if !fit_to_window(ctx, before, config).await? {
+        ctx.input.truncate(before);
+        return Ok(());
+    }
+
+    reply(ctx, config).await
+}
+
+/// Sends the conversation as it stands and appends the assistant's reply,
+/// streamed, boxed or plain depending on the model and config.
+pub(crate) async fn reply(
+    ctx: &mut ConversationContext,
+    config: &ConfigTC,
+) -> Result<(), Box<dyn Error>> {
+    for change in refresh_files(ctx, config.watch_files) {
+        print_message(&change, MessageType::System, config);
+    }
+
+    let started = Instant::now();
+    let before = ctx.input.len();
+    let mut usage = None;
+
+    if ctx.model.contains("claude") {
+        ctx.set_stream(false);
+
+        let reply: AnthropicMessage = anthropic_chat(ctx).await?;
+
+        let message = reply.content.first().ok_or("No content")?.text.clone();
+
+        if config.message_boxes_enabled {
+            print_message(&message, MessageType::Assistant, config);
+            println!();
+        } else {
+            println!("🤖 {}\n", message);
+        }
+        usage = reply.usage.as_ref().map(Usage::from);
+        if let Some(usage) = &usage {
+            print_usage(usage, config);
+        }
+        ctx.input.push(Message::new("assistant", message.clone()));
+
+        ctx.set_stream(true);
+    } else if ctx.model.starts_with("gemini") {
+        if config.enable_streaming {
+            gemini_stream(ctx).await?;
+        } else {
+            let response = gemini_chat(ctx).await?;
+            let reply = response.text();
+            ctx.input.push(Message::new("assistant", reply.clone()));
+            print_reply(&reply, config);
+            usage = response.usage_metadata.as_ref().map(Usage::from);
+            if let Some(usage) = &usage {
+                print_usage(usage, config);
+            }
+        }
+    } else if !config.enable_streaming
+        || ctx.model.eq_ignore_ascii_case("gpt-4o-search-preview")
+        || config.azure_deployment(&ctx.model).is_some()
+    {
+        ctx.set_stream(false);
+        let response: ResponseC = send_request("chat", ctx.to_request()).await?;
+        if let Some(choice) = response.choices.first() {
+            let reply = choice.message.content.clone();
+            ctx.input.push(Message::new("assistant", reply.clone()));
+            print_reply(&reply, config);
+        }
+        usage = response.usage.as_ref().map(Usage::from);
+        ctx.set_stream(true);
+    } else {
+        stream(ctx).await?;
+    }
+    stamp_reply(ctx, before, started.elapsed(), usage);
+
+    // The draft is already shown and kept, so a failed review only warns.
+    if let Some(critic_model) = &config.critic_model
+        && let Err(e) = critique_last_reply(ctx, critic_model, config).await
+    {
+        eprintln!("\nThe critic could not review this reply: {}\n", e);
+    }
+
+    Ok(())
+}
+
+/// Records the model, timing and usage on the reply just added, if any.
+fn stamp_reply(
+    ctx: &mut ConversationContext,
+    before: usize,
+    elapsed: Duration,
+    usage: Option<Usage>,
+) {
+    if ctx.input.len() <= before {
+        return;
+    }
+    let model = ctx.model.clone();
+    if let Some(last) = ctx.input.last_mut().filter(|m| m.role == "assistant") {
+        *last = Message::reply(&model, std::mem::take(&mut last.content), elapsed, usage);
+    }
+}
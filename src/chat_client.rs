@@ -7,8 +7,12 @@ use std::{
 };
 
 use crate::{
-    conversation::{AnthropicRequest, ConversationContext, DeltaData, Message},
+    conversation::{
+        AnthropicMessage, AnthropicRequest, ChatRequest, ConversationContext, DeltaData,
+        GeminiRequest, GeminiResponse, Message, ResponseC, Usage,
+    },
     spinner::run_with_spinner,
+    tc_config::{AzureConfig, get_config},
 };
 use futures_util::StreamExt;
 
@@ -17,6 +21,16 @@ const API_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
 const API_IMG_URL: &str = "https://api.openai.com/v1/images/generations";
 const ANTHROPIC_MODELS: &str = "https://api.anthropic.com/v1/models";
 const ANTHROPIC_MESSAGES: &str = "https://api.anthropic.com/v1/messages";
+const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// `GEMINI_BASE_URL` points the Gemini backend somewhere else, e.g. a local mock server.
+fn gemini_base_url() -> String {
+    env::var("GEMINI_BASE_URL").unwrap_or_else(|_| GEMINI_API_URL.to_string())
+}
+
+fn gemini_api_key() -> Result<String, Box<dyn Error>> {
+    Ok(env::var("GEMINI_API_KEY").map_err(|_| "GEMINI_API_KEY not set")?)
+}
 
 pub async fn get_models() -> Result<String, Box<dyn Error>> {
     let client = Client::new();
@@ -39,7 +53,7 @@ pub async fn stream(context: &mut ConversationContext) -> Result<(), Box<dyn Err
     print!("🤖 ");
     stdout().flush().ok();
 
-    let request_json = serde_json::to_string(context)?;
+    let request_json = serde_json::to_string(&context.to_request())?;
     let response = client
         .post(API_URL)
         .bearer_auth(&api_key)
@@ -58,19 +72,16 @@ pub async fn stream(context: &mut ConversationContext) -> Result<(), Box<dyn Err
         // println!("\n\ns:{s}\n\n");
 
         for p in s.split("data: ") {
-            if let Some(real) = p.split("event:").next() {
-                if let Ok(d) = serde_json::from_str::<DeltaData>(real.trim()) {
-                    print!("{}", d.delta);
-                    acc.push_str(&d.delta);
-                    stdout().flush().ok();
-                }
+            if let Some(real) = p.split("event:").next()
+                && let Ok(d) = serde_json::from_str::<DeltaData>(real.trim())
+            {
+                print!("{}", d.delta);
+                acc.push_str(&d.delta);
+                stdout().flush().ok();
             }
         }
     }
-    context.input.push(Message {
-        role: "assistant".into(),
-        content: acc,
-    });
+    context.input.push(Message::new("assistant", acc));
     println!("\n");
     Ok(())
 }
@@ -79,69 +90,341 @@ pub async fn anthropic_chat<T>(context: &ConversationContext) -> Result<T, Box<d
 where
     T: serde::de::DeserializeOwned,
 {
-    let client = Client::new();
-    let anthropic_request = AnthropicRequest::from_context(context, 2048);
+    let response_text = run_with_spinner(anthropic_request_text(context)).await?;
+
+    parse_response(&response_text)
+}
+
+async fn anthropic_request_text(context: &ConversationContext) -> Result<String, Box<dyn Error>> {
+    let anthropic_request =
+        AnthropicRequest::from_context(context, context.params.max_tokens.unwrap_or(2048) as usize);
     let request_json = serde_json::to_string(&anthropic_request)?;
     let api_key = env::var("ANTHROPIC_API_KEY")?;
 
-    let response_text = run_with_spinner(async {
-        client
-            .post(ANTHROPIC_MESSAGES)
-            .header("Content-Type", "application/json")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .body(request_json)
-            .send()
-            .await?
-            .text()
-            .await
-    })
-    .await?;
-
-    print!("\r                \r");
-    stdout().flush().ok();
+    Ok(Client::new()
+        .post(ANTHROPIC_MESSAGES)
+        .header("Content-Type", "application/json")
+        .header("x-api-key", api_key)
+        .header("anthropic-version", "2023-06-01")
+        .body(request_json)
+        .send()
+        .await?
+        .text()
+        .await?)
+}
 
-    let resp: T = from_str(&response_text)
+pub async fn gemini_chat(context: &ConversationContext) -> Result<GeminiResponse, Box<dyn Error>> {
+    let api_key = gemini_api_key()?;
+    let base_url = gemini_base_url();
+
+    let response = run_with_spinner(gemini_generate(&base_url, &api_key, context)).await?;
+
+    Ok(response)
+}
+
+pub(crate) async fn gemini_generate(
+    base_url: &str,
+    api_key: &str,
+    context: &ConversationContext,
+) -> Result<GeminiResponse, Box<dyn Error>> {
+    let request_json = serde_json::to_string(&GeminiRequest::from_context(context))?;
+    let url = format!("{}/models/{}:generateContent", base_url, context.model);
+
+    let response_text = Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("x-goog-api-key", api_key)
+        .body(request_json)
+        .send()
+        .await?
+        .text()
+        .await?;
+
+    let resp: GeminiResponse = from_str(&response_text)
         .map_err(|e| format!("Failed to parse response: {}\n{}", e, response_text))?;
+    if resp.candidates.is_empty() {
+        return Err(format!("No candidates in Gemini response:\n{}", response_text).into());
+    }
     Ok(resp)
 }
 
+pub async fn gemini_stream(context: &mut ConversationContext) -> Result<(), Box<dyn Error>> {
+    let api_key = gemini_api_key()?;
+    let url = format!(
+        "{}/models/{}:streamGenerateContent?alt=sse",
+        gemini_base_url(),
+        context.model
+    );
+
+    println!();
+    print!("🤖 ");
+    stdout().flush().ok();
+
+    let request_json = serde_json::to_string(&GeminiRequest::from_context(context))?;
+    let response = Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("x-goog-api-key", api_key)
+        .body(request_json)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Gemini request failed ({}):\n{}", status, body).into());
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut acc = String::new();
+
+    while let Some(next) = stream.next().await {
+        buffer.extend_from_slice(&next?);
+
+        // Events can be split across chunks, so only complete lines are parsed.
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            let line = std::str::from_utf8(&line)?.trim();
+            if let Some(data) = line.strip_prefix("data:")
+                && let Ok(chunk) = from_str::<GeminiResponse>(data.trim())
+            {
+                let text = chunk.text();
+                print!("{}", text);
+                acc.push_str(&text);
+                stdout().flush().ok();
+            }
+        }
+    }
+    context.input.push(Message::new("assistant", acc));
+    println!("\n");
+    Ok(())
+}
+
 pub async fn send_request<F, T>(url_flag: &str, context: F) -> Result<T, Box<dyn Error>>
 where
     F: serde::Serialize,
     T: serde::de::DeserializeOwned,
+{
+    let response_text = run_with_spinner(openai_request_text(url_flag, &context)).await?;
+
+    parse_response(&response_text)
+}
+
+async fn openai_request_text<F>(url_flag: &str, context: &F) -> Result<String, Box<dyn Error>>
+where
+    F: serde::Serialize,
 {
     let client = Client::new();
-    let api_key = env::var("OPENAI_API_KEY").map_err(|_| "OPENAI_API_KEY not set")?;
 
-    let mut request_json = serde_json::to_string(&context)?;
+    let mut body = serde_json::to_value(context)?;
+    let model = body
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let Some(fields) = body.as_object_mut() else {
+        return Err("Request must serialize to a JSON object".into());
+    };
     if url_flag == "chat" {
-        request_json = request_json.replace("\"input\":", "\"messages\":");
+        if let Some(input) = fields.remove("input") {
+            fields.insert("messages".into(), input);
+        }
+        if let Some(max_tokens) = fields.remove("max_output_tokens") {
+            fields.insert("max_completion_tokens".into(), max_tokens);
+        }
     }
-    let url = match url_flag {
-        "chat" => API_CHAT_URL,
-        "image" => API_IMG_URL,
-        _ => API_URL,
-    };
 
-    let response_text = run_with_spinner(async {
+    let config = get_config()?;
+    let request = if let Some((azure, deployment)) = config.azure_deployment(&model) {
+        let api_key =
+            env::var("AZURE_OPENAI_API_KEY").map_err(|_| "AZURE_OPENAI_API_KEY not set")?;
+        fields.insert("model".into(), deployment.into());
         client
-            .post(url)
-            .bearer_auth(&api_key)
-            .header("Content-Type", "application/json")
-            .body(request_json)
-            .send()
-            .await?
-            .text()
-            .await
-    })
-    .await?;
-
-    print!("\r                \r");
-    stdout().flush().ok();
+            .post(azure_url(azure, deployment, url_flag))
+            .header("api-key", api_key)
+    } else {
+        let api_key = env::var("OPENAI_API_KEY").map_err(|_| "OPENAI_API_KEY not set")?;
+        let url = match url_flag {
+            "chat" => API_CHAT_URL,
+            "image" => API_IMG_URL,
+            _ => API_URL,
+        };
+        client.post(url).bearer_auth(api_key)
+    };
+    let request_json = serde_json::to_string(&body)?;
 
-    let resp: T = from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {}\n{}", e, response_text))?;
+    Ok(request
+        .header("Content-Type", "application/json")
+        .body(request_json)
+        .send()
+        .await?
+        .text()
+        .await?)
+}
 
-    Ok(resp)
+fn parse_response<T>(response_text: &str) -> Result<T, Box<dyn Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    Ok(from_str(response_text)
+        .map_err(|e| format!("Failed to parse response: {}\n{}", e, response_text))?)
+}
+
+/// A finished reply from any provider.
+pub struct Completion {
+    pub text: String,
+    pub usage: Option<Usage>,
+}
+
+/// Sends `context` to whichever provider serves its model and waits for the
+/// whole reply. Prints nothing, so several can run side by side.
+pub async fn complete(context: &ConversationContext) -> Result<Completion, Box<dyn Error>> {
+    if context.model.contains("claude") {
+        let reply: AnthropicMessage = parse_response(&anthropic_request_text(context).await?)?;
+        Ok(Completion {
+            text: reply.content.first().ok_or("No content")?.text.clone(),
+            usage: reply.usage.as_ref().map(Usage::from),
+        })
+    } else if context.model.starts_with("gemini") {
+        let reply = gemini_generate(&gemini_base_url(), &gemini_api_key()?, context).await?;
+        Ok(Completion {
+            text: reply.text(),
+            usage: reply.usage_metadata.as_ref().map(Usage::from),
+        })
+    } else {
+        let request = ChatRequest {
+            stream: false,
+            ..context.to_request()
+        };
+        let reply: ResponseC = parse_response(&openai_request_text("chat", &request).await?)?;
+        Ok(Completion {
+            text: reply
+                .choices
+                .first()
+                .ok_or("No content")?
+                .message
+                .content
+                .clone(),
+            usage: reply.usage.as_ref().map(Usage::from),
+        })
+    }
+}
+
+fn azure_url(azure: &AzureConfig, deployment: &str, url_flag: &str) -> String {
+    let endpoint = azure.endpoint.trim_end_matches('/');
+    match url_flag {
+        "chat" => format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            endpoint, deployment, azure.api_version
+        ),
+        "image" => format!(
+            "{}/openai/deployments/{}/images/generations?api-version={}",
+            endpoint, deployment, azure.api_version
+        ),
+        _ => format!(
+            "{}/openai/responses?api-version={}",
+            endpoint, azure.api_version
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serves a single canned response and hands back the raw request it received.
+    fn mock_server(
+        body: &'static str,
+    ) -> Result<(String, thread::JoinHandle<String>), Box<dyn Error>> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?.to_string();
+
+        let handle = thread::spawn(move || {
+            let Ok((stream, _)) = listener.accept() else {
+                return String::new();
+            };
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap_or(0);
+                }
+                request.push_str(&line);
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).ok();
+            request.push_str(&String::from_utf8_lossy(&request_body));
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            (&stream).write_all(response.as_bytes()).ok();
+            request
+        });
+
+        Ok((format!("http://{addr}"), handle))
+    }
+
+    #[tokio::test]
+    async fn test_gemini_generate_against_mock_server() -> Result<(), Box<dyn Error>> {
+        let (base_url, handle) = mock_server(
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hello "},{"text":"there"}]}}],"usageMetadata":{"promptTokenCount":12,"candidatesTokenCount":3}}"#,
+        )?;
+
+        let mut ctx = ConversationContext::new("gemini-2.5-flash", false);
+        ctx.input.push(Message::new("developer", "be brief"));
+        ctx.input.push(Message::new("user", "hi"));
+        ctx.input.push(Message::new("assistant", "hello"));
+        ctx.input.push(Message::new("user", "again"));
+
+        let response = gemini_generate(&base_url, "test-key", &ctx).await?;
+        let request = handle.join().map_err(|_| "mock server panicked")?;
+
+        assert_eq!(response.text(), "Hello there");
+        assert_eq!(
+            response.usage_metadata.map(|u| u.prompt_token_count),
+            Some(12)
+        );
+
+        assert!(request.starts_with("POST /models/gemini-2.5-flash:generateContent "));
+        assert!(request.contains("x-goog-api-key: test-key"));
+        assert!(request.contains(r#""systemInstruction":{"parts":[{"text":"be brief"}]}"#));
+        assert!(request.contains(r#"{"role":"model","parts":[{"text":"hello"}]}"#));
+        assert!(!request.contains("developer"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_azure_urls_and_deployments() -> Result<(), Box<dyn Error>> {
+        let azure: AzureConfig = serde_json::from_str(
+            r#"{"endpoint":"https://res.openai.azure.com/","deployments":{"gpt-4o":"prod-4o"}}"#,
+        )?;
+        assert_eq!(azure.api_version, "2025-04-01-preview");
+        assert_eq!(azure.deployment("gpt-4o"), Some("prod-4o"));
+        assert_eq!(azure.deployment("o3-mini"), None);
+
+        assert_eq!(
+            azure_url(&azure, "prod-4o", "chat"),
+            "https://res.openai.azure.com/openai/deployments/prod-4o/chat/completions?api-version=2025-04-01-preview"
+        );
+        assert_eq!(
+            azure_url(&azure, "prod-4o", "image"),
+            "https://res.openai.azure.com/openai/deployments/prod-4o/images/generations?api-version=2025-04-01-preview"
+        );
+        assert_eq!(
+            azure_url(&azure, "prod-4o", "d"),
+            "https://res.openai.azure.com/openai/responses?api-version=2025-04-01-preview"
+        );
+        Ok(())
+    }
 }
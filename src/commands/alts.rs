@@ -0,0 +1,56 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message, print_reply};
+use crate::tc_config::get_config;
+
+pub async fn alts_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        if ctx.alternates.is_empty() {
+            print_message(
+                "No earlier answers. Use :retry to get one.",
+                MessageType::System,
+                &config,
+            );
+            return Ok(());
+        }
+
+        let Some(n) = cc.args.first().and_then(|a| a.parse::<usize>().ok()) else {
+            let mut output = String::from("Earlier answers (use :alts <n> to restore one):\n");
+            for (i, alt) in ctx.alternates.iter().enumerate() {
+                output.push_str(&format!(
+                    "{}) [{}] {}\n",
+                    i + 1,
+                    alt.model,
+                    alt.message.preview(50)
+                ));
+            }
+            print_message(&output, MessageType::System, &config);
+            return Ok(());
+        };
+
+        if n == 0 || n > ctx.alternates.len() {
+            eprintln!("\nNo earlier answer {}.\n", n);
+            return Ok(());
+        }
+
+        // Swap so the answer being replaced becomes an alternate itself.
+        let chosen = ctx.alternates[n - 1].message.clone();
+        let Some(current) = ctx.input.last_mut().filter(|m| m.role == "assistant") else {
+            eprintln!("\nThe conversation does not end with an answer.\n");
+            return Ok(());
+        };
+        let restored = std::mem::replace(current, chosen);
+        let model = ctx.model.clone();
+        let alt = &mut ctx.alternates[n - 1];
+        alt.message = restored;
+        alt.model = model;
+
+        if let Some(last) = ctx.input.last() {
+            print_reply(&last.content, &config);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::ConversationContext;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn branches_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        if ctx.branches.is_empty() {
+            print_message(
+                "No branches yet. Use :edit <n> to start one.",
+                MessageType::System,
+                &config,
+            );
+            return Ok(());
+        }
+
+        let mut output = String::from("Branches:\n");
+        print_tree(&ctx, None, 0, &mut output);
+        print_message(&output, MessageType::System, &config);
+    }
+    Ok(())
+}
+
+fn print_tree(ctx: &ConversationContext, parent: Option<usize>, depth: usize, output: &mut String) {
+    for (id, branch) in ctx.branches.iter().enumerate() {
+        if branch.parent != parent {
+            continue;
+        }
+        let marker = if id == ctx.active_branch { "*" } else { " " };
+        output.push_str(&format!(
+            "{}{}{:>2}) {} ({} messages, forked at {})\n",
+            marker,
+            "  ".repeat(depth),
+            id,
+            branch.label,
+            ctx.branch_messages(id).len(),
+            branch.fork_at
+        ));
+        print_tree(ctx, Some(id), depth + 1, output);
+    }
+}
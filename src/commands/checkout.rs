@@ -0,0 +1,30 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn checkout_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let Some(id) = cc.args.first().and_then(|a| a.parse::<usize>().ok()) else {
+            eprintln!("\nInvalid use of {}. Usage: {} <branch>\n", cc.cmd, cc.cmd);
+            return Ok(());
+        };
+
+        let mut ctx = cc.conversation_context.lock().await;
+        ctx.checkout(id)?;
+
+        let last = ctx
+            .input
+            .iter()
+            .rev()
+            .find(|m| m.role != "developer")
+            .map(|m| format!("\n{}: {}", m.role, m.preview(50)))
+            .unwrap_or_default();
+        print_message(
+            &format!("Switched to branch {}{}", id, last),
+            MessageType::System,
+            &get_config()?,
+        );
+    }
+    Ok(())
+}
@@ -11,8 +11,10 @@ pub async fn clear_command(cc: Option<CommandContext>) -> CommandResult {
     {
         if let Some(cc) = cc {
             let mut ctx = cc.conversation_context.lock().await;
-            ctx.input.clear();
-            ctx.input.push((*cc.dev_message).clone());
+            let pinned: Vec<_> = ctx.input.drain(..).filter(|m| m.pinned).collect();
+            ctx.input.push(cc.dev_message.lock().await.clone());
+            ctx.input.extend(pinned);
+            ctx.reset_branches();
         }
 
         Command::new("clear").status()?;
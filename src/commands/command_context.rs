@@ -6,7 +6,7 @@ use crate::conversation::{ConversationContext, Message};
 #[derive(Clone)]
 pub struct CommandContext {
     pub conversation_context: Arc<Mutex<ConversationContext>>,
-    pub dev_message: Arc<Message>,
+    pub dev_message: Arc<Mutex<Message>>,
     pub cmd: String,
     pub args: Vec<String>,
 }
@@ -14,7 +14,7 @@ pub struct CommandContext {
 impl CommandContext {
     pub fn new(
         conversation_context: Arc<Mutex<ConversationContext>>,
-        dev_message: Arc<Message>,
+        dev_message: Arc<Mutex<Message>>,
         cmd: String,
         args: Vec<String>,
     ) -> Self {
@@ -1,11 +1,16 @@
 use std::{collections::HashMap, sync::LazyLock};
 
 use crate::commands::{
-    change_model::change_model_command, clear::clear_command, clear_config::dc,
-    command_tc::CommandTC, debug::debug_command, document::document_command,
-    edit_config::ec_command, gf::gf_command, help::help_command, image::image_command,
-    load_conversation::lc_command, quit::quit_command, readme::readme_command,
-    save_conversation::sc_command, sh,
+    alts::alts_command, branches::branches_command, change_model::change_model_command,
+    checkout::checkout_command, clear::clear_command, clear_config::dc, command_tc::CommandTC,
+    commitmsg::commitmsg_command, compare::compare_command, critic::critic_command,
+    ctx::ctx_command, debug::debug_command, diff::diff_command, document::document_command,
+    edit::edit_command, edit_config::ec_command, export::export_command, gf::gf_command,
+    help::help_command, image::image_command, load_conversation::lc_command,
+    persona::persona_command, pin::pin_command, quit::quit_command, readme::readme_command,
+    retry::retry_command, review::review_command, save_conversation::sc_command,
+    search::search_command, sh, template::template_command, undo::undo_command,
+    unpin::unpin_command, watch::watch_command,
 };
 
 macro_rules! register_command {
@@ -28,7 +33,7 @@ pub static TC_COMMANDS: LazyLock<HashMap<&str, CommandTC>> = LazyLock::new(|| {
     register_command!("dc", "Deletes the current application config file.", dc, r);
     register_command!(
         "lc",
-        "Loads a conversation from the conversations directory.",
+        "Browses saved conversations, or loads one directly. Usage: lc [name]",
         lc_command,
         r
     );
@@ -38,6 +43,18 @@ pub static TC_COMMANDS: LazyLock<HashMap<&str, CommandTC>> = LazyLock::new(|| {
         sc_command,
         r
     );
+    register_command!(
+        "search",
+        "Searches saved conversations. Usage: search <query>",
+        search_command,
+        r
+    );
+    register_command!(
+        "export",
+        "Exports the conversation. Usage: export <md|html|jsonl> [path]",
+        export_command,
+        r
+    );
     register_command!(
         "clear",
         "Clears the conversation context.",
@@ -57,6 +74,108 @@ pub static TC_COMMANDS: LazyLock<HashMap<&str, CommandTC>> = LazyLock::new(|| {
         image_command,
         r
     );
+    register_command!(
+        "compare",
+        "Asks several models the same prompt. Usage: compare <model1> <model2> [model3]",
+        compare_command,
+        r
+    );
+    register_command!(
+        "critic",
+        "Reviews each reply with a second model. Usage: critic <model> [draft|revised|all] | off",
+        critic_command,
+        r
+    );
+    register_command!(
+        "persona",
+        "Switches persona. Usage: persona [name | save <name> | assign <name> | unassign]",
+        persona_command,
+        r
+    );
+    register_command!(
+        "pin",
+        "Keeps a message through :clear and context trimming. Usage: pin <n>",
+        pin_command,
+        r
+    );
+    register_command!(
+        "unpin",
+        "Unpins one message, or all of them. Usage: unpin [n]",
+        unpin_command,
+        r
+    );
+    register_command!(
+        "diff",
+        "Adds a git diff to the context. Usage: diff [--staged | <range>]",
+        diff_command,
+        r
+    );
+    register_command!(
+        "commitmsg",
+        "Drafts a commit message from the staged changes and offers to commit.",
+        commitmsg_command,
+        r
+    );
+    register_command!(
+        "review",
+        "Reviews a git diff. Usage: review [range], e.g. review main..HEAD",
+        review_command,
+        r
+    );
+    register_command!(
+        "watch",
+        "Keeps files added with gf in sync with disk. Usage: watch [replace|annotate|off]",
+        watch_command,
+        r
+    );
+    register_command!(
+        "t",
+        "Expands a prompt template and sends it. Usage: t <template> [input]",
+        template_command,
+        r
+    );
+    register_command!(
+        "ctx",
+        "Lists context messages with token estimates. Usage: ctx [drop <n | n..m>]",
+        ctx_command,
+        r
+    );
+    register_command!(
+        "undo",
+        "Removes the last question and its answer.",
+        undo_command,
+        r
+    );
+    register_command!(
+        "retry",
+        "Regenerates the last answer. Usage: retry [model]",
+        retry_command,
+        r
+    );
+    register_command!(
+        "alts",
+        "Lists answers replaced by retry. Usage: alts [n] to restore one",
+        alts_command,
+        r
+    );
+    register_command!(
+        "edit",
+        "Rewrites an earlier message on a new branch. Usage: edit <n>",
+        edit_command,
+        r
+    );
+    register_command!(
+        "branches",
+        "Lists the conversation branches.",
+        branches_command,
+        r
+    );
+    register_command!(
+        "checkout",
+        "Switches to another branch. Usage: checkout <branch>",
+        checkout_command,
+        r
+    );
     register_command!(
         "sh",
         "Executes a program with arguments. Usage: sh <program> [args...]",
@@ -0,0 +1,51 @@
+use crate::chat_client::complete;
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::{ConversationContext, Message};
+use crate::git::{confirm_diff_size, diff, format_diff, git};
+use crate::message_printer::{MessageType, print_message};
+use crate::spinner::run_with_spinner;
+use crate::tc_config::get_config;
+use crate::templates::prompt;
+use crate::utils::confirm_action;
+
+pub async fn commitmsg_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let config = get_config()?;
+        let files = diff(&["--staged"])?;
+        if files.is_empty() {
+            print_message(
+                "Nothing is staged. Stage changes with git add first.",
+                MessageType::System,
+                &config,
+            );
+            return Ok(());
+        }
+
+        let staged = Message::new("user", format_diff(&files));
+        if !confirm_diff_size(
+            files.len(),
+            staged.estimated_tokens(),
+            config.gf_token_budget,
+        ) {
+            return Ok(());
+        }
+
+        let model = cc.conversation_context.lock().await.model.clone();
+        let mut request = ConversationContext::new(&model, false);
+        request
+            .input
+            .push(Message::new("developer", prompt("commit_prompt")?));
+        request.input.push(staged);
+
+        let response = run_with_spinner(complete(&request)).await?;
+        let message = response.text.trim();
+        print_message("Suggested commit message", MessageType::System, &config);
+        println!("\n{}\n", message);
+
+        if confirm_action("Commit the staged changes with this message? (y/n): ") {
+            print!("{}", git(&["commit", "-m", message])?);
+        }
+    }
+    Ok(())
+}
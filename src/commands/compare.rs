@@ -0,0 +1,98 @@
+use crate::chat_client::{Completion, complete};
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::{ConversationContext, Message};
+use crate::message_printer::{MessageType, print_message, print_reply};
+use crate::spinner::run_with_spinner;
+use crate::tc_config::get_config;
+use crate::utils::read_user_input;
+use futures_util::future::join_all;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+pub async fn compare_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        if !(2..=3).contains(&cc.args.len()) {
+            eprintln!(
+                "\nInvalid use of {}. Usage: {} <model1> <model2> [model3]\n",
+                cc.cmd, cc.cmd
+            );
+            return Ok(());
+        }
+
+        let config = get_config()?;
+        if let Some(unknown) = cc.args.iter().find(|m| !config.all_models.contains(m)) {
+            eprintln!("\nUnknown model: {}. Use :cm to list models.\n", unknown);
+            return Ok(());
+        }
+
+        let prompt = read_user_input("Prompt: ")?;
+        if prompt.is_empty() {
+            return Ok(());
+        }
+
+        let mut ctx = cc.conversation_context.lock().await;
+        let contexts: Vec<ConversationContext> = cc
+            .args
+            .iter()
+            .map(|model| {
+                let mut candidate = ctx.clone();
+                candidate.model = model.clone();
+                candidate.input.push(Message::new("user", prompt.clone()));
+                candidate
+            })
+            .collect();
+
+        let results = run_with_spinner(join_all(contexts.iter().map(timed_complete))).await;
+
+        for (i, (model, (result, elapsed))) in cc.args.iter().zip(&results).enumerate() {
+            let mut header = format!("[{}] {} · {:.1}s", i + 1, model, elapsed.as_secs_f64());
+            if let Ok(Completion {
+                usage: Some(usage), ..
+            }) = result
+            {
+                header.push_str(&format!(
+                    " · {} in / {} out tokens",
+                    usage.input_tokens, usage.output_tokens
+                ));
+            }
+            print_message(&header, MessageType::System, &config);
+            match result {
+                Ok(completion) => print_reply(&completion.text, &config),
+                Err(e) => eprintln!("\nRequest failed: {}\n", e),
+            }
+        }
+
+        let choice = read_user_input("Keep which answer? (number, Enter to discard): ")?;
+        match choice.parse::<usize>() {
+            Ok(n) if n > 0 && n <= results.len() => {
+                if let (Ok(completion), elapsed) = &results[n - 1] {
+                    ctx.input.push(Message::new("user", prompt));
+                    ctx.input.push(Message::reply(
+                        &cc.args[n - 1],
+                        completion.text.clone(),
+                        *elapsed,
+                        completion.usage,
+                    ));
+                    print_message(
+                        &format!("Kept answer from {}", cc.args[n - 1]),
+                        MessageType::System,
+                        &config,
+                    );
+                } else {
+                    eprintln!("\nThat request failed. Nothing was added.\n");
+                }
+            }
+            _ => print_message("Answers discarded", MessageType::System, &config),
+        }
+    }
+    Ok(())
+}
+
+async fn timed_complete(
+    context: &ConversationContext,
+) -> (Result<Completion, Box<dyn Error>>, Duration) {
+    let start = Instant::now();
+    let result = complete(context).await;
+    (result, start.elapsed())
+}
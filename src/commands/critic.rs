@@ -0,0 +1,56 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::{CriticKeep, GLOBAL_CONFIG, get_config, write_config};
+
+pub async fn critic_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut config = get_config()?;
+
+        match cc.args.first().map(String::as_str) {
+            None => {}
+            Some("off") => config.critic_model = None,
+            Some(model) => {
+                if !config.all_models.iter().any(|m| m == model) {
+                    eprintln!("\nUnknown model: {}. Use :cm to list models.\n", model);
+                    return Ok(());
+                }
+                config.critic_model = Some(model.to_string());
+
+                if let Some(keep) = cc.args.get(1) {
+                    config.critic_keep = match keep.as_str() {
+                        "draft" => CriticKeep::Draft,
+                        "revised" => CriticKeep::Revised,
+                        "all" => CriticKeep::All,
+                        _ => {
+                            eprintln!(
+                                "\nInvalid use of {}. Usage: {} <model> [draft|revised|all] | off\n",
+                                cc.cmd, cc.cmd
+                            );
+                            return Ok(());
+                        }
+                    };
+                }
+            }
+        }
+
+        if !cc.args.is_empty() {
+            {
+                let mut cg = GLOBAL_CONFIG.write()?;
+                cg.critic_model = config.critic_model.clone();
+                cg.critic_keep = config.critic_keep;
+            }
+            write_config(&get_config()?, false)?;
+        }
+
+        let status = match &config.critic_model {
+            Some(model) => format!(
+                "Critic: {} (keeping {:?} answers)",
+                model, config.critic_keep
+            ),
+            None => "Critic: off".to_string(),
+        };
+        print_message(&status, MessageType::System, &config);
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::ConversationContext;
+use crate::message_printer::{MessageType, print_message};
+use crate::model_catalog::context_window;
+use crate::tc_config::get_config;
+use std::ops::RangeInclusive;
+
+pub async fn ctx_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        match cc
+            .args
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [] => print_message(&inspect(&ctx), MessageType::System, &config),
+            ["drop", range] => {
+                let Some(range) = parse_range(range) else {
+                    eprintln!("\nInvalid range: {}. Use n or n..m.\n", range);
+                    return Ok(());
+                };
+                if *range.end() >= ctx.input.len() {
+                    eprintln!(
+                        "\nNo message {}. Run {} to list them.\n",
+                        range.end(),
+                        cc.cmd
+                    );
+                    return Ok(());
+                }
+                if range.clone().any(|i| ctx.input[i].role == "developer") {
+                    eprintln!("\nThe developer message cannot be dropped.\n");
+                    return Ok(());
+                }
+
+                let freed: usize = ctx.input[range.clone()]
+                    .iter()
+                    .map(|m| m.estimated_tokens())
+                    .sum();
+                let count = range.clone().count();
+                if *range.end() + 1 == ctx.input.len() {
+                    ctx.alternates.clear();
+                }
+                ctx.input.drain(range);
+                print_message(
+                    &format!("Dropped {} message(s), ~{} tokens freed", count, freed),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            _ => eprintln!(
+                "\nInvalid use of {}. Usage: {} [drop <n | n..m>]\n",
+                cc.cmd, cc.cmd
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn inspect(ctx: &ConversationContext) -> String {
+    let mut output = format!(
+        "{:>3}  {:<9} {:<7} {:>7}  {}\n",
+        "#", "role", "source", "tokens", "preview"
+    );
+    let mut total = 0;
+    for (i, msg) in ctx.input.iter().enumerate() {
+        let tokens = msg.estimated_tokens();
+        total += tokens;
+        output.push_str(&format!(
+            "{:>3}{} {:<9} {:<7} {:>7}  {}\n",
+            i,
+            if msg.pinned { "*" } else { " " },
+            msg.role,
+            msg.source.label(),
+            tokens,
+            msg.preview(50)
+        ));
+    }
+    let window = context_window(&ctx.model);
+    output.push_str(&format!(
+        "\nTotal ~{} tokens, {:.0}% of {}'s {} token window. * = pinned",
+        total,
+        total as f64 * 100.0 / window as f64,
+        ctx.model,
+        window
+    ));
+    output
+}
+
+/// Parses `n` or the inclusive range `n..m`.
+fn parse_range(arg: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = match arg.split_once("..") {
+        Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+        None => {
+            let n = arg.parse().ok()?;
+            (n, n)
+        }
+    };
+    (start <= end).then_some(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::{Message, MessageSource};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("3"), Some(3..=3));
+        assert_eq!(parse_range("2..5"), Some(2..=5));
+        assert_eq!(parse_range("5..2"), None);
+        assert_eq!(parse_range("2.."), None);
+        assert_eq!(parse_range("x"), None);
+    }
+
+    #[test]
+    fn test_inspect_lists_sources_and_total() {
+        let mut ctx = ConversationContext::new("gpt-4o", false);
+        ctx.input.push(Message::new("developer", "Be brief."));
+        ctx.input.push(Message {
+            pinned: true,
+            ..Message::from_file("src/run.rs", "fn run() {}", MessageSource::Gf)
+        });
+        ctx.input.push(Message::new("user", "What does run do?"));
+        ctx.input.push(Message::reply(
+            "gpt-4o",
+            "Nothing yet.",
+            Duration::ZERO,
+            None,
+        ));
+
+        let output = inspect(&ctx);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].starts_with("  0  developer typed"));
+        assert!(lines[2].starts_with("  1* user      gf"));
+        assert!(lines[2].ends_with("src/run.rs"));
+        assert!(lines[4].starts_with("  3  assistant reply"));
+        assert!(lines[4].ends_with("Nothing yet."));
+
+        let total: usize = ctx.input.iter().map(Message::estimated_tokens).sum();
+        assert!(output.contains(&format!("Total ~{} tokens", total)));
+    }
+}
@@ -8,7 +8,8 @@ pub async fn debug_command(cc: Option<CommandContext>) -> CommandResult {
         println!("\nCurrent model: {}", ctx.model);
         println!("\nCurrent context messages:\n");
         for msg in &ctx.input {
-            println!("{}:\n{}\n:::\n", msg.role, msg.content);
+            let pin = if msg.pinned { " 📌 pinned" } else { "" };
+            println!("{}{}:\n{}\n:::\n", msg.role, pin, msg.content);
         }
 
         // Get the current config
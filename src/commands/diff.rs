@@ -0,0 +1,37 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::{Message, MessageSource};
+use crate::git::{confirm_diff_size, diff, diff_summary, format_diff};
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn diff_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let args: Vec<&str> = cc.args.iter().map(String::as_str).collect();
+        let files = diff(&args)?;
+        let config = get_config()?;
+        if files.is_empty() {
+            print_message("No changes to add.", MessageType::System, &config);
+            return Ok(());
+        }
+
+        let title = format!("git diff {}", args.join(" "));
+        let message =
+            Message::from_file(title.trim_end(), &format_diff(&files), MessageSource::Diff);
+        if !confirm_diff_size(
+            files.len(),
+            message.estimated_tokens(),
+            config.gf_token_budget,
+        ) {
+            println!("Nothing added.\n");
+            return Ok(());
+        }
+        cc.conversation_context.lock().await.input.push(message);
+        print_message(
+            &format!("Added {} files:\n{}", files.len(), diff_summary(&files)),
+            MessageType::System,
+            &config,
+        );
+    }
+    Ok(())
+}
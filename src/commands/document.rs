@@ -1,8 +1,8 @@
 use crate::chat_client::send_request;
 use crate::commands::command_context::CommandContext;
 use crate::conversation::{ConversationContext, Message};
-use crate::messages::MESSAGES;
 use crate::preview_md::preview_markdown;
+use crate::templates::prompt;
 use crate::utils::{confirm_action, extract_message_text};
 use std::fs::{self, File};
 use std::io::Write;
@@ -15,13 +15,7 @@ pub async fn document_command(cc: Option<CommandContext>) -> CommandResult {
         let ctx = cc.conversation_context.lock().await;
         let mut new_context = ConversationContext::new("o3-mini", false);
 
-        let dev_message = Message {
-            role: "developer".into(),
-            content: MESSAGES
-                .get("document_prompt")
-                .ok_or("Missing document_prompt")?
-                .to_string(),
-        };
+        let dev_message = Message::new("developer", prompt("document_prompt")?);
         new_context.input.push(dev_message);
 
         for msg in &ctx.input {
@@ -30,22 +24,18 @@ pub async fn document_command(cc: Option<CommandContext>) -> CommandResult {
             }
         }
 
-        let response = send_request("d", &new_context).await?;
+        let response = send_request("d", new_context.to_request()).await?;
         let report =
             extract_message_text(&response).ok_or("No content received in the document report")?;
 
         let mut title_context = ConversationContext::new("gpt-4o", false);
-        let title_prompt = Message {
-            role: "developer".into(),
-            content: format!(
-                "{} \n::\n {}",
-                MESSAGES.get("title_prompt").ok_or("Missing title_prompt")?,
-                report
-            ),
-        };
+        let title_prompt = Message::new(
+            "developer",
+            format!("{} \n::\n {}", prompt("title_prompt")?, report),
+        );
         title_context.input.push(title_prompt);
 
-        let title_response = send_request("d", &title_context).await?;
+        let title_response = send_request("d", title_context.to_request()).await?;
         let title = extract_message_text(&title_response).unwrap_or_else(|| "Report".to_string());
 
         let sanitized_title = title
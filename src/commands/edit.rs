@@ -0,0 +1,63 @@
+use crate::chat::send_user_message;
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+use crate::utils::read_user_input_with;
+
+pub async fn edit_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        let Some(n) = cc.args.first().and_then(|a| a.parse::<usize>().ok()) else {
+            let mut output = String::from("User messages:\n");
+            for (i, msg) in ctx.input.iter().enumerate() {
+                if msg.role == "user" {
+                    output.push_str(&format!("{:>3}) {}\n", i, msg.preview(50)));
+                }
+            }
+            print_message(&output, MessageType::System, &config);
+            eprintln!("\nUsage: {} <n>\n", cc.cmd);
+            return Ok(());
+        };
+
+        let Some(original) = ctx
+            .input
+            .get(n)
+            .filter(|m| m.role == "user")
+            .map(|m| m.content.clone())
+        else {
+            eprintln!("\nMessage {} is not a user message.\n", n);
+            return Ok(());
+        };
+
+        let edited = read_user_input_with("Edit: ", &original)?;
+        if edited.is_empty() || edited == original {
+            println!("\nNothing changed.\n");
+            return Ok(());
+        }
+
+        let label: String = edited
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(40)
+            .collect();
+        // The edit only stays on its branch once it has been answered.
+        let previous = ctx.active_branch;
+        let id = ctx.fork(n, &label);
+        let result = send_user_message(&mut ctx, edited, &config).await;
+        if result.is_err() || ctx.input.last().is_none_or(|m| m.role != "assistant") {
+            ctx.abandon_fork(previous)?;
+            return result;
+        }
+        print_message(
+            &format!("Started branch {} from message {}", id, n),
+            MessageType::System,
+            &config,
+        );
+    }
+    Ok(())
+}
@@ -21,6 +21,7 @@ pub async fn ec_command(cc: Option<CommandContext>) -> CommandResult {
                 }
             }
         }
+        cc.dev_message.lock().await.content = config.dev_message.clone();
         ctx.model = config.model.clone();
         write_config(&config, false)?;
 
@@ -0,0 +1,47 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::export::{ExportFormat, default_title, export};
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+use crate::utils::{format_timestamp, now_secs};
+use std::fs;
+use std::path::PathBuf;
+
+pub async fn export_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let Some(format) = cc.args.first().and_then(|f| ExportFormat::parse(f)) else {
+            eprintln!(
+                "\nInvalid use of {}. Usage: {} <md|html|jsonl> [path]\n",
+                cc.cmd, cc.cmd
+            );
+            return Ok(());
+        };
+        let config = get_config()?;
+        let ctx = cc.conversation_context.lock().await;
+
+        let path = match cc.args.get(1) {
+            Some(path) => PathBuf::from(path),
+            None => PathBuf::from("exports").join(format!(
+                "conversation-{}.{}",
+                format_timestamp(now_secs())
+                    .replace(['-', ':'], "")
+                    .replace(' ', "-"),
+                format.extension()
+            )),
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(
+            &path,
+            export(&ctx, &default_title(&ctx), format, &config.theme)?,
+        )?;
+        print_message(
+            &format!("Exported to {}", path.display()),
+            MessageType::System,
+            &config,
+        );
+    }
+    Ok(())
+}
@@ -1,36 +1,69 @@
 use crate::commands::command_context::CommandContext;
 use crate::commands::command_tc::CommandResult;
-use crate::conversation::Message;
+use crate::conversation::{Message, MessageSource};
 use crate::message_printer::{MessageType, print_message};
 use crate::tc_config::get_config;
-use std::fs;
-use std::path::Path;
+use crate::utils::{confirm_action, format_size};
+use crate::walker::expand_file_args;
 
 pub async fn gf_command(cc: Option<CommandContext>) -> CommandResult {
     if let Some(cc) = cc {
-        let mut ctx = cc.conversation_context.lock().await;
         if cc.args.is_empty() {
             eprintln!(
-                "\nInvalid use of {}. Usage: {} <path1> <path2> ...\n",
+                "\nInvalid use of {}. Usage: {} <file | dir [extensions...] | glob> ...\n",
                 cc.cmd, cc.cmd
             );
             return Ok(());
         }
+        let config = get_config()?;
 
-        for path in cc.args {
-            let trimmed_path = path.trim();
-            match fs::read_to_string(Path::new(trimmed_path)) {
-                Ok(content) => {
-                    ctx.input.push(Message {
-                        role: "user".to_string(),
-                        content: format!("{}\n\n:::\n\n{}", trimmed_path, content),
-                    });
-                    let message = format!("Added: {trimmed_path}");
-                    print_message(&message, MessageType::System, &get_config()?);
-                }
-                Err(e) => eprintln!("Error reading {}: {}\n", trimmed_path, e),
-            }
+        let (set, errors) = expand_file_args(&cc.args);
+        for error in &errors {
+            eprintln!("Error reading {}", error);
         }
+        if let Some(report) = set.skipped_report() {
+            print_message(&report, MessageType::System, &config);
+        }
+        let files = set.files;
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let messages: Vec<Message> = files
+            .iter()
+            .map(|(origin, content)| Message::from_disk(origin.clone(), content, MessageSource::Gf))
+            .collect();
+        let total_tokens: usize = messages.iter().map(Message::estimated_tokens).sum();
+
+        if total_tokens > config.gf_token_budget
+            && !confirm_action(&format!(
+                "\n{} files come to ~{} tokens, over the budget of {}. Add them anyway? (y/n): ",
+                files.len(),
+                total_tokens,
+                config.gf_token_budget
+            ))
+        {
+            println!("Nothing added.\n");
+            return Ok(());
+        }
+
+        let mut table = format!("{:>10}  {:>8}  path", "size", "tokens");
+        for ((origin, content), message) in files.iter().zip(&messages) {
+            table.push_str(&format!(
+                "\n{:>10}  {:>8}  {}",
+                format_size(content.len()),
+                message.estimated_tokens(),
+                origin.label()
+            ));
+        }
+        table.push_str(&format!(
+            "\nAdded {} files, ~{} tokens",
+            files.len(),
+            total_tokens
+        ));
+
+        cc.conversation_context.lock().await.input.extend(messages);
+        print_message(&table, MessageType::System, &config);
     }
     Ok(())
 }
@@ -9,7 +9,7 @@ use std::{error::Error, sync::Arc};
 pub async fn handle_command(
     cmd: &str,
     context: Arc<Mutex<ConversationContext>>,
-    dev_message: Arc<Message>,
+    dev_message: Arc<Mutex<Message>>,
 ) -> Result<(), Box<dyn Error>> {
     let cmd_string = cmd.trim();
     let mut parts = cmd_string.split_whitespace();
@@ -1,20 +1,46 @@
+use crate::browser::browse_conversations;
 use crate::commands::command_context::CommandContext;
 use crate::commands::command_tc::CommandResult;
-use crate::conversation::ConversationContext;
-use crate::utils::read_user_input;
-use std::fs;
+use crate::message_printer::{MessageType, print_message};
+use crate::storage::{is_plain_name, read_context};
+use crate::tc_config::get_config;
+use std::path::Path;
 
 pub async fn lc_command(cc: Option<CommandContext>) -> CommandResult {
     if let Some(cc) = cc {
-        let mut ctx = cc.conversation_context.lock().await;
-
-        let convo_name = read_user_input("\nProvide conversation name: ")?;
+        let dir = Path::new("conversations");
+        let config = get_config()?;
 
-        let as_str = fs::read_to_string(format!("conversations/{convo_name}.json"))?;
-
-        let new_context: ConversationContext = serde_json::from_str(&as_str)?;
+        let (name, new_context) = if cc.args.is_empty() {
+            match browse_conversations(dir)? {
+                Some(chosen) => chosen,
+                None => return Ok(()),
+            }
+        } else {
+            let name = cc.args.join(" ");
+            if !is_plain_name(&name) {
+                eprintln!("\nConversation names cannot contain '/', '\\' or '..'.\n");
+                return Ok(());
+            }
+            let path = dir.join(format!("{}.json", name));
+            if !path.exists() {
+                eprintln!(
+                    "\nNo saved conversation named '{}'. Run {} without a name to browse.\n",
+                    name, cc.cmd
+                );
+                return Ok(());
+            }
+            let context = read_context(&path)?;
+            (name, context)
+        };
 
+        let mut ctx = cc.conversation_context.lock().await;
         *ctx = new_context;
+        print_message(
+            &format!("Loaded {} ({} messages)", name, ctx.input.len()),
+            MessageType::System,
+            &config,
+        );
     }
     Ok(())
 }
@@ -1,18 +1,37 @@
+pub mod alts;
+pub mod branches;
 pub mod change_model;
+pub mod checkout;
 pub mod clear;
 pub mod clear_config;
 pub mod command_context;
 pub mod command_tc;
 pub mod commands_registry;
+pub mod commitmsg;
+pub mod compare;
+pub mod critic;
+pub mod ctx;
 pub mod debug;
+pub mod diff;
 pub mod document;
+pub mod edit;
 pub mod edit_config;
+pub mod export;
 pub mod gf;
 pub mod handle_commands;
 pub mod help;
 pub mod image;
 pub mod load_conversation;
+pub mod persona;
+pub mod pin;
 pub mod quit;
 pub mod readme;
+pub mod retry;
+pub mod review;
 pub mod save_conversation;
+pub mod search;
 pub mod sh;
+pub mod template;
+pub mod undo;
+pub mod unpin;
+pub mod watch;
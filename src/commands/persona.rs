@@ -0,0 +1,97 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::persona::{
+    INVALID_NAME, Persona, assign_persona, get_personas_dir, is_valid_name, project_persona,
+};
+use crate::tc_config::get_config;
+use std::env;
+
+pub async fn persona_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let config = get_config()?;
+        let cwd = env::current_dir()?;
+
+        match cc
+            .args
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [] => {
+                let personas = Persona::list();
+                let mut output = if personas.is_empty() {
+                    format!(
+                        "No personas yet. Save the current setup with :persona save <name>, or add files to {}\n",
+                        get_personas_dir().display()
+                    )
+                } else {
+                    String::from("Personas:\n")
+                };
+                for persona in &personas {
+                    output.push_str(&format!("  {}\n", persona.summary_line()));
+                }
+                if let Some(name) = project_persona(&cwd) {
+                    output.push_str(&format!("\nThis project uses: {}", name));
+                }
+                print_message(&output, MessageType::System, &config);
+            }
+            ["save", name] => {
+                if !is_valid_name(name) {
+                    eprintln!("\n{}\n", INVALID_NAME);
+                    return Ok(());
+                }
+                let ctx = cc.conversation_context.lock().await;
+                let persona = Persona {
+                    name: name.to_string(),
+                    dev_message: cc.dev_message.lock().await.content.clone(),
+                    model: Some(ctx.model.clone()),
+                    temperature: ctx.params.temperature,
+                    max_tokens: ctx.params.max_tokens,
+                };
+                let path = persona.save()?;
+                print_message(
+                    &format!("Saved persona {} to {}", name, path.display()),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            ["assign", name] => {
+                Persona::load(name)?;
+                assign_persona(&cwd, Some(name))?;
+                print_message(
+                    &format!("New sessions in {} will use {}", cwd.display(), name),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            ["unassign"] => {
+                assign_persona(&cwd, None)?;
+                print_message(
+                    &format!("Removed the persona assignment for {}", cwd.display()),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            [name] => {
+                let persona = Persona::load(name)?;
+                let mut ctx = cc.conversation_context.lock().await;
+                let mut dev_message = cc.dev_message.lock().await;
+                if let Some(warning) = persona.apply(&mut ctx, &mut dev_message, &config) {
+                    eprintln!("\n{}\n", warning);
+                }
+                print_message(
+                    &format!("Switched to {} ({})", persona.name, ctx.model),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            _ => eprintln!(
+                "\nInvalid use of {}. Usage: {} [name | save <name> | assign <name> | unassign]\n",
+                cc.cmd, cc.cmd
+            ),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn pin_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        let Some(n) = cc.args.first().and_then(|a| a.parse::<usize>().ok()) else {
+            let mut output = String::from("Messages (* = pinned):\n");
+            for (i, msg) in ctx.input.iter().enumerate() {
+                if msg.role != "developer" {
+                    let marker = if msg.pinned { "*" } else { " " };
+                    output.push_str(&format!(
+                        "{}{:>3}) {:<9} {}\n",
+                        marker,
+                        i,
+                        msg.role,
+                        msg.preview(50)
+                    ));
+                }
+            }
+            print_message(&output, MessageType::System, &config);
+            eprintln!("\nUsage: {} <n>\n", cc.cmd);
+            return Ok(());
+        };
+
+        match ctx.input.get_mut(n) {
+            Some(msg) if msg.role == "developer" => {
+                eprintln!("\nThe developer message is always kept.\n");
+            }
+            Some(msg) => {
+                msg.pinned = true;
+                let preview = msg.preview(50);
+                print_message(
+                    &format!("Pinned {}: {}", n, preview),
+                    MessageType::System,
+                    &config,
+                );
+            }
+            None => eprintln!("\nNo message {}. Run {} to list them.\n", n, cc.cmd),
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use crate::chat_client::send_request;
 use crate::commands::command_context::CommandContext;
 use crate::commands::command_tc::CommandResult;
-use crate::conversation::{ConversationContext, Message};
-use crate::messages::MESSAGES;
+use crate::conversation::{ConversationContext, Message, MessageSource};
 use crate::preview_md::preview_markdown;
-use crate::utils::{confirm_action, extract_message_text, read_user_input, walk_directory};
+use crate::templates::prompt;
+use crate::utils::{confirm_action, extract_message_text, read_user_input};
+use crate::walker::walk_directory;
 use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::Write;
@@ -31,27 +32,23 @@ pub async fn readme_command(cc: Option<CommandContext>) -> CommandResult {
         };
 
         let mut new_context = ConversationContext::new("o3-mini", false);
-        let dev_message = Message {
-            role: "developer".into(),
-            content: MESSAGES.get("readme").unwrap_or(&"").to_string(),
-        };
+        let dev_message = Message::new("developer", prompt("readme")?);
         new_context.input.push(dev_message);
 
-        let mut excluded_dirs = HashSet::new();
-        excluded_dirs.insert("target");
-
         let mut names = vec![];
-        let files = walk_directory(&dir, &extensions, &excluded_dirs)?;
+        let set = walk_directory(Path::new(&dir), &extensions);
+        if let Some(report) = set.skipped_report() {
+            println!("\n{}", report);
+        }
 
-        for (path, content) in files {
-            names.push(path.clone());
-            new_context.input.push(Message {
-                role: "user".to_string(),
-                content: format!("{}\n\n:::\n\n{}", path, content),
-            });
+        for (origin, content) in set.files {
+            names.push(origin.path.clone());
+            new_context
+                .input
+                .push(Message::from_disk(origin, &content, MessageSource::Readme));
         }
         println!("\nFiles used: {:?}\n\n", names);
-        let response = send_request("d", &new_context).await?;
+        let response = send_request("d", new_context.to_request()).await?;
 
         let result_content = if let Some(r) = extract_message_text(&response) {
             r.replace("•", "-")
@@ -0,0 +1,59 @@
+use crate::chat::reply;
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::Alternate;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn retry_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        let model = match cc.args.first() {
+            Some(model) if !config.all_models.contains(model) => {
+                eprintln!("\nUnknown model: {}. Use :cm to list models.\n", model);
+                return Ok(());
+            }
+            Some(model) => model.clone(),
+            None => ctx.model.clone(),
+        };
+
+        let len = ctx.input.len();
+        if len < 2 || ctx.input[len - 1].role != "assistant" || ctx.input[len - 2].role != "user" {
+            eprintln!("\nThere is no answer to retry.\n");
+            return Ok(());
+        }
+
+        let Some(previous) = ctx.input.pop() else {
+            return Ok(());
+        };
+        let alternate = Alternate {
+            model: ctx.model.clone(),
+            message: previous,
+        };
+
+        // A different model only answers this once, the conversation keeps its own.
+        let before = ctx.input.len();
+        let original_model = std::mem::replace(&mut ctx.model, model);
+        let result = reply(&mut ctx, &config).await;
+        ctx.model = original_model;
+
+        // Without a new answer, the old one goes back in its place.
+        if result.is_err() || ctx.input.len() == before {
+            ctx.input.truncate(before);
+            ctx.input.push(alternate.message);
+            return result;
+        }
+        ctx.alternates.push(alternate);
+        print_message(
+            &format!(
+                "{} earlier answer(s) kept. Use :alts to see them.",
+                ctx.alternates.len()
+            ),
+            MessageType::System,
+            &config,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::chat_client::complete;
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::{ConversationContext, Message, MessageSource};
+use crate::git::{confirm_diff_size, diff, diff_summary, format_diff};
+use crate::message_printer::{MessageType, print_message, print_reply, print_usage};
+use crate::spinner::run_with_spinner;
+use crate::tc_config::get_config;
+use crate::templates::prompt;
+use std::time::Instant;
+
+pub async fn review_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        // Without a range, everything not yet committed.
+        let range: Vec<&str> = if cc.args.is_empty() {
+            vec!["HEAD"]
+        } else {
+            cc.args.iter().map(String::as_str).collect()
+        };
+        let config = get_config()?;
+        let files = diff(&range)?;
+        if files.is_empty() {
+            print_message("No changes to review.", MessageType::System, &config);
+            return Ok(());
+        }
+        let diff_message = Message::from_file(
+            &format!("git diff {}", range.join(" ")),
+            &format_diff(&files),
+            MessageSource::Diff,
+        );
+        if !confirm_diff_size(
+            files.len(),
+            diff_message.estimated_tokens(),
+            config.gf_token_budget,
+        ) {
+            println!("Nothing reviewed.\n");
+            return Ok(());
+        }
+        print_message(
+            &format!("Reviewing {} files:\n{}", files.len(), diff_summary(&files)),
+            MessageType::System,
+            &config,
+        );
+
+        let mut ctx = cc.conversation_context.lock().await;
+        let mut request = ConversationContext::new(&ctx.model, false);
+        request
+            .input
+            .push(Message::new("developer", prompt("review_prompt")?));
+        request.input.push(diff_message.clone());
+
+        let started = Instant::now();
+        let response = run_with_spinner(complete(&request)).await?;
+        print_reply(&response.text, &config);
+        if let Some(usage) = &response.usage {
+            print_usage(usage, &config);
+        }
+
+        // Kept in the conversation so the review can be discussed.
+        let model = ctx.model.clone();
+        ctx.input.push(diff_message);
+        ctx.input.push(Message {
+            source: MessageSource::Tool,
+            ..Message::new(
+                "user",
+                format!("Review the changes in {}.", range.join(" ")),
+            )
+        });
+        ctx.input.push(Message::reply(
+            &model,
+            response.text,
+            started.elapsed(),
+            response.usage,
+        ));
+    }
+    Ok(())
+}
@@ -1,27 +1,28 @@
 use crate::commands::command_context::CommandContext;
 use crate::commands::command_tc::CommandResult;
+use crate::storage::{ConversationFile, ConversationMeta, is_plain_name};
 use crate::utils::read_user_input;
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::Path;
+use std::path::PathBuf;
 
 pub async fn sc_command(cc: Option<CommandContext>) -> CommandResult {
     if let Some(cc) = cc {
         let current_convo = cc.conversation_context.lock().await;
 
-        let as_json = serde_json::to_string(&*current_convo)?;
-
         let convo_name = read_user_input("Conversation name: ")?;
-
-        if !Path::exists(Path::new("conversations")) {
-            fs::create_dir("conversations")?;
+        if !is_plain_name(&convo_name) {
+            eprintln!(
+                "\nInvalid use of {}. Conversation names cannot be empty or contain '/', '\\' or '..'.\n",
+                cc.cmd
+            );
+            return Ok(());
         }
 
-        let path = format!("conversations/{convo_name}.json");
+        let path = PathBuf::from(format!("conversations/{convo_name}.json"));
 
-        let mut file = File::create(&path).map_err(|_| "Could not create file")?;
-        file.write_all(as_json.as_bytes())
-            .map_err(|_| "Could not write to file")?;
+        let meta = ConversationMeta::new(&current_convo, Some(convo_name));
+        ConversationFile::new(&current_convo, meta)
+            .write(&path)
+            .map_err(|e| format!("Could not write to file: {}", e))?;
     }
     Ok(())
 }
@@ -0,0 +1,48 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::search::{format_hit, search};
+use crate::storage::read_context;
+use crate::tc_config::get_config;
+use crate::utils::read_user_input;
+
+pub async fn search_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        if cc.args.is_empty() {
+            eprintln!("\nInvalid use of {}. Usage: {} <query>\n", cc.cmd, cc.cmd);
+            return Ok(());
+        }
+        let config = get_config()?;
+
+        let hits = search(&cc.args.join(" "), 10)?;
+        if hits.is_empty() {
+            print_message("No matches found.", MessageType::System, &config);
+            return Ok(());
+        }
+
+        println!();
+        for (i, hit) in hits.iter().enumerate() {
+            println!("{}\n", format_hit(i + 1, hit));
+        }
+
+        let choice = read_user_input("Load which hit? (number, Enter to skip): ")?;
+        let Some(hit) = choice
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| hits.get(i))
+        else {
+            return Ok(());
+        };
+
+        let new_context = read_context(&hit.path)?;
+        let mut ctx = cc.conversation_context.lock().await;
+        *ctx = new_context;
+        print_message(
+            &format!("Loaded {}", hit.path.display()),
+            MessageType::System,
+            &config,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::chat::send_user_message;
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+use crate::templates::{expand, get_templates_dir, needs_input, template, template_names};
+use crate::utils::read_user_input;
+
+pub async fn template_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let config = get_config()?;
+
+        let Some(name) = cc.args.first() else {
+            print_message(
+                &format!(
+                    "Templates (add your own as .md files in {}):\n{}",
+                    get_templates_dir().display(),
+                    template_names().join("\n")
+                ),
+                MessageType::System,
+                &config,
+            );
+            eprintln!("\nUsage: {} <template> [input]\n", cc.cmd);
+            return Ok(());
+        };
+
+        let Some(text) = template(name) else {
+            eprintln!(
+                "\nNo template named '{}'. Run {} to list them.\n",
+                name, cc.cmd
+            );
+            return Ok(());
+        };
+
+        let mut input = cc.args[1..].join(" ");
+        if input.is_empty() && needs_input(&text) {
+            input = read_user_input("Input: ")?;
+        }
+        let prompt = expand(&text, &input)?;
+
+        print_message(&prompt, MessageType::User, &config);
+        let mut ctx = cc.conversation_context.lock().await;
+        send_user_message(&mut ctx, prompt, &config).await?;
+    }
+    Ok(())
+}
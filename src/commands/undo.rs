@@ -0,0 +1,85 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::conversation::{Message, MessageSource};
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn undo_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+
+        let Some((start, question)) = last_turn(&ctx.input) else {
+            eprintln!("\nNothing to undo.\n");
+            return Ok(());
+        };
+
+        let prompt = ctx.input[question].preview(50);
+        ctx.input.truncate(start);
+        ctx.alternates.clear();
+        print_message(
+            &format!("Removed: {}", prompt),
+            MessageType::System,
+            &get_config()?,
+        );
+    }
+    Ok(())
+}
+
+/// Where the last turn starts, counting the `@path` attachments sent with
+/// it, and the index of the typed question. A turn is that question and
+/// its answer, or a question that never got one, e.g. after a failed
+/// request. Pinned, loaded and summary messages are never a turn.
+fn last_turn(messages: &[Message]) -> Option<(usize, usize)> {
+    let mut question = messages.len().checked_sub(1)?;
+    if messages[question].role == "assistant" {
+        question = question.checked_sub(1)?;
+    }
+    let msg = &messages[question];
+    if msg.role != "user" || msg.source != MessageSource::Typed || msg.pinned {
+        return None;
+    }
+
+    let mut start = question;
+    while start > 0 && messages[start - 1].source == MessageSource::Mention {
+        start -= 1;
+    }
+    Some((start, question))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mention() -> Message {
+        Message::from_file("src/run.rs", "fn run() {}", MessageSource::Mention)
+    }
+
+    #[test]
+    fn test_last_turn() {
+        let mut messages = vec![
+            Message::new("developer", "dev"),
+            Message::new("user", "q1"),
+            Message::new("assistant", "a1"),
+            mention(),
+            mention(),
+            Message::new("user", "what does @src/run.rs do?"),
+            Message::new("assistant", "a2"),
+        ];
+        assert_eq!(last_turn(&messages), Some((3, 5)));
+
+        // An unanswered question.
+        messages.pop();
+        assert_eq!(last_turn(&messages), Some((3, 5)));
+
+        messages.truncate(3);
+        assert_eq!(last_turn(&messages), Some((1, 1)));
+
+        messages.push(Message::from_file("Cargo.toml", "", MessageSource::Gf));
+        assert_eq!(last_turn(&messages), None);
+
+        messages.pop();
+        messages[1].pinned = true;
+        assert_eq!(last_turn(&messages), None);
+        assert_eq!(last_turn(&[]), None);
+    }
+}
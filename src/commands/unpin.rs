@@ -0,0 +1,35 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::get_config;
+
+pub async fn unpin_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        let mut ctx = cc.conversation_context.lock().await;
+        let config = get_config()?;
+
+        let message = match cc.args.first() {
+            None => {
+                let mut count = 0;
+                for msg in ctx.input.iter_mut().filter(|m| m.pinned) {
+                    msg.pinned = false;
+                    count += 1;
+                }
+                format!("Unpinned {} message(s)", count)
+            }
+            Some(arg) => match arg.parse::<usize>().ok().and_then(|n| ctx.input.get_mut(n)) {
+                Some(msg) if msg.pinned => {
+                    msg.pinned = false;
+                    format!("Unpinned {}: {}", arg, msg.preview(50))
+                }
+                Some(_) => format!("Message {} is not pinned", arg),
+                None => {
+                    eprintln!("\nInvalid use of {}. Usage: {} [n]\n", cc.cmd, cc.cmd);
+                    return Ok(());
+                }
+            },
+        };
+        print_message(&message, MessageType::System, &config);
+    }
+    Ok(())
+}
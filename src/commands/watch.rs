@@ -0,0 +1,36 @@
+use crate::commands::command_context::CommandContext;
+use crate::commands::command_tc::CommandResult;
+use crate::message_printer::{MessageType, print_message};
+use crate::tc_config::{GLOBAL_CONFIG, WatchMode, get_config, write_config};
+
+pub async fn watch_command(cc: Option<CommandContext>) -> CommandResult {
+    if let Some(cc) = cc {
+        if let Some(arg) = cc.args.first() {
+            let mode = match arg.as_str() {
+                "off" => WatchMode::Off,
+                "replace" | "on" => WatchMode::Replace,
+                "annotate" => WatchMode::Annotate,
+                _ => {
+                    eprintln!(
+                        "\nInvalid use of {}. Usage: {} [replace|annotate|off]\n",
+                        cc.cmd, cc.cmd
+                    );
+                    return Ok(());
+                }
+            };
+            GLOBAL_CONFIG.write()?.watch_files = mode;
+            write_config(&get_config()?, false)?;
+        }
+
+        let config = get_config()?;
+        let status = match config.watch_files {
+            WatchMode::Off => "Watch: off".to_string(),
+            mode => format!(
+                "Watch: {:?}. Files added with :gf are checked before each send.",
+                mode
+            ),
+        };
+        print_message(&status, MessageType::System, &config);
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::templates::template_names;
+use linefeed::complete::{Completer, Completion, PathCompleter};
+use linefeed::prompter::Prompter;
+use linefeed::terminal::Terminal;
+
+/// Completes template names after `:t`, paths in commands, and paths after
+/// `@` in chat lines. Other words in a chat line are left alone.
+pub(crate) struct TcCompleter;
+
+impl<Term: Terminal> Completer<Term> for TcCompleter {
+    fn complete(
+        &self,
+        word: &str,
+        prompter: &Prompter<Term>,
+        start: usize,
+        end: usize,
+    ) -> Option<Vec<Completion>> {
+        let before = &prompter.buffer()[..start];
+        if before.trim_end() == ":t" {
+            return Some(
+                template_names()
+                    .into_iter()
+                    .filter(|name| name.starts_with(word))
+                    .map(Completion::simple)
+                    .collect(),
+            );
+        }
+        let is_command = prompter.buffer().starts_with(':');
+        // `@` is a word break, so `word` is what follows it.
+        let is_mention = before.ends_with('@') && !before.ends_with("\\@");
+        if is_command || is_mention {
+            PathCompleter.complete(word, prompter, start, end)
+        } else {
+            None
+        }
+    }
+}
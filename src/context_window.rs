@@ -0,0 +1,180 @@
+use crate::chat_client::complete;
+use crate::conversation::{ConversationContext, Message, MessageSource};
+use crate::message_printer::{MessageType, print_message};
+use crate::model_catalog::context_window;
+use crate::spinner::run_with_spinner;
+use crate::tc_config::{ConfigTC, ContextStrategy};
+use crate::templates::prompt;
+use std::error::Error;
+
+// Start trimming once the conversation uses this share of the window,
+// and trim down to the lower mark so it does not happen every turn.
+const TRIM_AT_PERCENT: usize = 80;
+const TRIM_TO_PERCENT: usize = 50;
+
+pub(crate) fn estimated_tokens(messages: &[Message]) -> usize {
+    messages.iter().map(Message::estimated_tokens).sum()
+}
+
+/// Makes room in `ctx` before it is sent, following `context_strategy`.
+/// The turn being sent, which starts at `turn_start`, is never trimmed.
+/// Returns false when the request should not be sent at all.
+pub(crate) async fn fit_to_window(
+    ctx: &mut ConversationContext,
+    turn_start: usize,
+    config: &ConfigTC,
+) -> Result<bool, Box<dyn Error>> {
+    let window = context_window(&ctx.model);
+    let total = estimated_tokens(&ctx.input);
+    if total <= window * TRIM_AT_PERCENT / 100 {
+        return Ok(true);
+    }
+
+    if config.context_strategy == ContextStrategy::Refuse {
+        eprintln!(
+            "\nThe conversation is ~{} tokens, near the {} token window of {}. Use :clear or drop some context first.\n",
+            total, window, ctx.model
+        );
+        return Ok(false);
+    }
+
+    let selected = select_oldest(
+        &ctx.input,
+        turn_start,
+        total - window * TRIM_TO_PERCENT / 100,
+    );
+    if selected.is_empty() {
+        eprintln!(
+            "\nThe conversation is ~{} tokens, but everything left is pinned or part of this turn. Sending anyway.\n",
+            total
+        );
+        return Ok(true);
+    }
+
+    let removed: Vec<Message> = selected.iter().map(|&i| ctx.input[i].clone()).collect();
+    // Summarize before removing anything, so a failed request leaves the
+    // conversation as it was.
+    let summary = match config.context_strategy {
+        ContextStrategy::Summarize => Some(summarize(&ctx.model, &removed).await?),
+        _ => None,
+    };
+    let first = selected[0];
+    for &i in selected.iter().rev() {
+        ctx.input.remove(i);
+    }
+
+    let mut report = format!(
+        "Context is near the {} token window of {}.\n",
+        window, ctx.model
+    );
+    match summary {
+        Some(summary) => {
+            report.push_str(&format!(
+                "Summarized {} messages (~{} tokens) into ~{} tokens:\n",
+                removed.len(),
+                estimated_tokens(&removed),
+                summary.estimated_tokens()
+            ));
+            ctx.input.insert(first, summary);
+        }
+        None => report.push_str(&format!(
+            "Dropped {} messages (~{} tokens):\n",
+            removed.len(),
+            estimated_tokens(&removed)
+        )),
+    }
+    for msg in &removed {
+        report.push_str(&format!("- {}: {}\n", msg.role, msg.preview(50)));
+    }
+    print_message(report.trim_end(), MessageType::System, config);
+
+    Ok(true)
+}
+
+/// Picks the oldest turns before `turn_start` that may be trimmed until at
+/// least `needed` tokens are freed. A turn is the user's messages and the
+/// replies to them, taken whole so no reply is left without its question.
+/// The developer message and turns with a pinned message always stay.
+fn select_oldest(messages: &[Message], turn_start: usize, needed: usize) -> Vec<usize> {
+    let mut freed = 0;
+    let mut selected = Vec::new();
+
+    let mut start = 0;
+    while start < turn_start && freed < needed {
+        if messages[start].role == "developer" {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < turn_start && !matches!(messages[end].role.as_str(), "assistant" | "developer")
+        {
+            end += 1;
+        }
+        while end < turn_start && messages[end].role == "assistant" {
+            end += 1;
+        }
+
+        let turn = &messages[start..end];
+        if !turn.iter().any(|m| m.pinned) {
+            freed += estimated_tokens(turn);
+            selected.extend(start..end);
+        }
+        start = end;
+    }
+    selected
+}
+
+async fn summarize(model: &str, removed: &[Message]) -> Result<Message, Box<dyn Error>> {
+    let mut transcript = String::new();
+    for msg in removed {
+        transcript.push_str(&format!("{}:\n{}\n\n", msg.role, msg.content));
+    }
+
+    let mut summary_context = ConversationContext::new(model, false);
+    summary_context
+        .input
+        .push(Message::new("developer", prompt("summary_prompt")?));
+    summary_context.input.push(Message::new("user", transcript));
+
+    let completion = run_with_spinner(complete(&summary_context)).await?;
+
+    Ok(Message {
+        source: MessageSource::Summary,
+        ..Message::new(
+            "user",
+            format!("Summary of earlier conversation:\n\n{}", completion.text),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(role: &str, tokens: usize) -> Message {
+        Message::new(role, "x".repeat(tokens * 4))
+    }
+
+    #[test]
+    fn test_select_oldest_skips_developer_pinned_and_new_turn() {
+        let mut pinned = sized("user", 100);
+        pinned.pinned = true;
+        let messages = vec![
+            sized("developer", 100),
+            pinned,
+            sized("assistant", 100),
+            sized("user", 100),
+            sized("assistant", 100),
+            sized("user", 100),
+            sized("assistant", 100),
+            // The turn being sent: an attachment and the question.
+            sized("user", 1000),
+            sized("user", 100),
+        ];
+
+        // Whole turns only, so a reply never loses its question.
+        assert_eq!(select_oldest(&messages, 7, 50), vec![3, 4]);
+        assert_eq!(select_oldest(&messages, 7, 10_000), vec![3, 4, 5, 6]);
+        assert!(select_oldest(&messages, 7, 0).is_empty());
+    }
+}
@@ -1,24 +1,269 @@
+use crate::branches::Branch;
+use crate::utils::now_secs;
+use crate::watch::FileOrigin;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// A message as tc keeps and saves it. Providers only ever see `role` and
+/// `content`, see `WireMessage` and the Anthropic and Gemini request types.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct Message {
     pub role: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "MessageSource::is_typed")]
+    pub source: MessageSource,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// The model that wrote an assistant message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Seconds since the epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    /// How long the model took to answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// The file a `:gf` message was read from, for watch mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileOrigin>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Where a message came from. Never sent to a provider.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageSource {
+    #[default]
+    Typed,
+    Gf,
+    Readme,
+    Summary,
+    /// Attached with `@path` in a chat line.
+    Mention,
+    /// A git diff added with `:diff` or `:review`.
+    Diff,
+    /// A model's answer.
+    Reply,
+    /// An answer rewritten by the critic model.
+    Critic,
+    /// Written by a command on the user's behalf, such as the request
+    /// `:review` adds.
+    Tool,
+}
+
+impl MessageSource {
+    fn is_typed(&self) -> bool {
+        *self == MessageSource::Typed
+    }
+
+    /// Loaded into the context rather than written in the conversation.
+    pub fn is_loaded(&self) -> bool {
+        !matches!(
+            self,
+            MessageSource::Typed
+                | MessageSource::Reply
+                | MessageSource::Critic
+                | MessageSource::Tool
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageSource::Typed => "typed",
+            MessageSource::Gf => "gf",
+            MessageSource::Readme => "readme",
+            MessageSource::Summary => "summary",
+            MessageSource::Mention => "@path",
+            MessageSource::Diff => "diff",
+            MessageSource::Reply => "reply",
+            MessageSource::Critic => "critic",
+            MessageSource::Tool => "tool",
+        }
+    }
+}
+
+impl Message {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_file(path: &str, content: &str, source: MessageSource) -> Self {
+        Self {
+            role: "user".into(),
+            content: format!("{}\n\n:::\n\n{}", path, content),
+            source,
+            ..Default::default()
+        }
+    }
+
+    /// Like `from_file`, remembering where the content came from so it can
+    /// be refreshed when the file changes.
+    pub fn from_disk(origin: FileOrigin, content: &str, source: MessageSource) -> Self {
+        let label = origin.label();
+        Self {
+            file: Some(origin),
+            ..Self::from_file(&label, content, source)
+        }
+    }
+
+    /// An assistant message, stamped with the model that wrote it, when,
+    /// how long it took and what it cost.
+    pub fn reply(
+        model: &str,
+        content: impl Into<String>,
+        elapsed: Duration,
+        usage: Option<Usage>,
+    ) -> Self {
+        Self {
+            model: Some(model.into()),
+            created: Some(now_secs()),
+            elapsed_ms: Some(elapsed.as_millis() as u64),
+            usage,
+            source: MessageSource::Reply,
+            ..Self::new("assistant", content)
+        }
+    }
+
+    /// Rough token count, about four characters per token plus a little
+    /// per-message overhead. Good enough to budget against a context window.
+    pub fn estimated_tokens(&self) -> usize {
+        self.content.chars().count().div_ceil(4) + 4
+    }
+
+    /// The first line of the message, shortened to `max_chars`.
+    pub fn preview(&self, max_chars: usize) -> String {
+        let line = self
+            .content
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim();
+        if line.chars().count() > max_chars {
+            format!("{}…", line.chars().take(max_chars).collect::<String>())
+        } else {
+            line.to_string()
+        }
+    }
+
+    /// File contents loaded into context stay the same turn after turn,
+    /// which makes them worth caching once they are big enough.
+    fn is_cacheable(&self) -> bool {
+        self.source.is_loaded() && self.content.len() >= CACHE_MIN_CHARS
+    }
+}
+
+/// The conversation being worked on. Saved through `storage::ConversationFile`,
+/// and turned into a provider request with `to_request` or `from_context`.
+#[derive(Deserialize, Debug, Clone)]
 pub struct ConversationContext {
     pub model: String,
     pub input: Vec<Message>,
     pub stream: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<Branch>,
+    #[serde(default)]
+    pub active_branch: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<Alternate>,
+    #[serde(default)]
+    pub params: RequestParams,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Sampling settings sent with every request, usually set by a persona.
+/// Unset values leave the provider's defaults alone.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct RequestParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl RequestParams {
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// An earlier answer to the last user message, replaced by `:retry`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Alternate {
+    pub model: String,
+    pub message: Message,
+}
+
+/// The OpenAI request body. Only the fields the API knows about, so
+/// `ConversationContext` can carry local state such as branches.
+#[derive(Serialize, Debug)]
+pub struct ChatRequest<'a> {
+    pub model: &'a str,
+    pub input: Vec<WireMessage<'a>>,
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Renamed to `max_completion_tokens` for chat completions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct WireMessage<'a> {
+    pub role: &'a str,
+    pub content: &'a str,
+}
+
+// Anthropic only caches prefixes of roughly 1024 tokens or more.
+const CACHE_MIN_CHARS: usize = 4096;
+// Anthropic allows four breakpoints per request, one goes to the system prompt.
+const MAX_MESSAGE_BREAKPOINTS: usize = 3;
+
+#[derive(Serialize, Debug)]
 pub struct AnthropicRequest {
-    pub system: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system: Vec<AnthropicTextBlock>,
     pub model: String,
-    pub messages: Vec<Message>,
+    pub messages: Vec<AnthropicRequestMessage>,
     pub max_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnthropicRequestMessage {
+    pub role: String,
+    pub content: Vec<AnthropicTextBlock>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnthropicTextBlock {
+    #[serde(rename = "type")]
+    pub type_field: &'static str,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CacheControl {
+    #[serde(rename = "type")]
+    pub type_field: &'static str,
+}
+
+impl AnthropicTextBlock {
+    fn new(text: &str, cached: bool) -> Self {
+        Self {
+            type_field: "text",
+            text: text.into(),
+            cache_control: cached.then_some(CacheControl {
+                type_field: "ephemeral",
+            }),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,36 +274,227 @@ pub struct AnthropicMessageContent {
 #[derive(Debug, Deserialize)]
 pub struct AnthropicMessage {
     pub content: Vec<AnthropicMessageContent>,
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+/// Token counts for a single reply, whichever provider produced it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+}
+
+impl From<&AnthropicUsage> for Usage {
+    fn from(u: &AnthropicUsage) -> Self {
+        Self {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+            cache_read_tokens: u.cache_read_input_tokens.unwrap_or(0),
+            cache_creation_tokens: u.cache_creation_input_tokens.unwrap_or(0),
+        }
+    }
 }
 
 impl AnthropicRequest {
     pub fn from_context(ctx: &ConversationContext, max_tokens: usize) -> Self {
-        let system_content = ctx
+        let system = ctx
             .input
             .iter()
-            .find(|m| m.role == "developer")
-            .map(|m| m.content.clone())
+            .find(|m| m.role == "developer" && !m.content.is_empty())
+            .map(|m| vec![AnthropicTextBlock::new(&m.content, true)])
             .unwrap_or_default();
+
+        let messages: Vec<&Message> = ctx.input.iter().filter(|m| m.role != "developer").collect();
+
+        // Caching is prefix based, so marking the last few large context
+        // messages covers everything loaded before them as well.
+        let breakpoints: Vec<usize> = messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.is_cacheable())
+            .map(|(i, _)| i)
+            .rev()
+            .take(MAX_MESSAGE_BREAKPOINTS)
+            .collect();
+
         Self {
-            system: system_content,
+            system,
             model: ctx.model.clone(),
             max_tokens,
-            messages: ctx
+            temperature: ctx.params.temperature,
+            messages: messages
+                .iter()
+                .enumerate()
+                .map(|(i, m)| AnthropicRequestMessage {
+                    role: m.role.clone(),
+                    content: vec![AnthropicTextBlock::new(
+                        &m.content,
+                        breakpoints.contains(&i),
+                    )],
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<GeminiContent>,
+    pub contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GeminiGenerationConfig>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiGenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub parts: Vec<GeminiPart>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GeminiPart {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiResponse {
+    #[serde(default)]
+    pub candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    pub usage_metadata: Option<GeminiUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GeminiCandidate {
+    #[serde(default)]
+    pub content: GeminiContent,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiUsage {
+    #[serde(default)]
+    pub prompt_token_count: u64,
+    #[serde(default)]
+    pub candidates_token_count: u64,
+}
+
+impl From<&GeminiUsage> for Usage {
+    fn from(u: &GeminiUsage) -> Self {
+        Self {
+            input_tokens: u.prompt_token_count,
+            output_tokens: u.candidates_token_count,
+            ..Default::default()
+        }
+    }
+}
+
+impl GeminiContent {
+    fn text(role: Option<&str>, text: &str) -> Self {
+        Self {
+            role: role.map(String::from),
+            parts: vec![GeminiPart { text: text.into() }],
+        }
+    }
+}
+
+impl GeminiRequest {
+    pub fn from_context(ctx: &ConversationContext) -> Self {
+        let system_instruction = ctx
+            .input
+            .iter()
+            .find(|m| m.role == "developer" && !m.content.is_empty())
+            .map(|m| GeminiContent::text(None, &m.content));
+
+        Self {
+            system_instruction,
+            contents: ctx
                 .input
                 .iter()
                 .filter(|m| m.role != "developer")
-                .cloned()
+                .map(|m| {
+                    let role = if m.role == "assistant" {
+                        "model"
+                    } else {
+                        "user"
+                    };
+                    GeminiContent::text(Some(role), &m.content)
+                })
                 .collect(),
+            generation_config: (!ctx.params.is_unset()).then_some(GeminiGenerationConfig {
+                temperature: ctx.params.temperature,
+                max_output_tokens: ctx.params.max_tokens,
+            }),
         }
     }
 }
 
+impl GeminiResponse {
+    pub fn text(&self) -> String {
+        self.candidates
+            .first()
+            .map(|c| c.content.parts.iter().map(|p| p.text.as_str()).collect())
+            .unwrap_or_default()
+    }
+}
+
 impl ConversationContext {
     pub fn new(model: &str, stream: bool) -> Self {
         Self {
             model: model.into(),
             input: Vec::new(),
             stream,
+            branches: Vec::new(),
+            active_branch: 0,
+            alternates: Vec::new(),
+            params: RequestParams::default(),
+        }
+    }
+
+    pub fn to_request(&self) -> ChatRequest<'_> {
+        ChatRequest {
+            model: &self.model,
+            input: self
+                .input
+                .iter()
+                .map(|m| WireMessage {
+                    role: &m.role,
+                    content: &m.content,
+                })
+                .collect(),
+            stream: self.stream,
+            temperature: self.params.temperature,
+            max_output_tokens: self.params.max_tokens,
         }
     }
 
@@ -75,6 +511,26 @@ pub struct Response {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ResponseC {
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub usage: Option<ChatUsage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChatUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+}
+
+impl From<&ChatUsage> for Usage {
+    fn from(u: &ChatUsage) -> Self {
+        Self {
+            input_tokens: u.prompt_tokens,
+            output_tokens: u.completion_tokens,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -104,3 +560,58 @@ pub struct OutputContent {
 pub struct DeltaData {
     pub delta: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_marks(request: &AnthropicRequest) -> Vec<bool> {
+        request
+            .messages
+            .iter()
+            .map(|m| m.content[0].cache_control.is_some())
+            .collect()
+    }
+
+    #[test]
+    fn test_from_context_cache_breakpoints() {
+        let big = "x".repeat(CACHE_MIN_CHARS);
+        let mut ctx = ConversationContext::new("claude", false);
+        ctx.input.push(Message::new("developer", "be nice"));
+        for i in 0..4 {
+            ctx.input.push(Message::from_file(
+                &format!("f{i}.rs"),
+                &big,
+                MessageSource::Gf,
+            ));
+        }
+        ctx.input.push(Message::from_file(
+            "small.rs",
+            "fn main() {}",
+            MessageSource::Gf,
+        ));
+        ctx.input.push(Message::new("user", big.clone()));
+
+        let request = AnthropicRequest::from_context(&ctx, 100);
+
+        assert!(request.system[0].cache_control.is_some());
+        // Only the last three large file messages are marked, typed input never is.
+        assert_eq!(
+            cache_marks(&request),
+            vec![false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_from_context_without_developer_message() {
+        let mut ctx = ConversationContext::new("claude", false);
+        ctx.input.push(Message::new("user", "hi"));
+
+        let request = AnthropicRequest::from_context(&ctx, 100);
+        let json = serde_json::to_string(&request).unwrap_or_default();
+
+        assert!(request.system.is_empty());
+        assert!(!json.contains("system"));
+        assert!(!json.contains("cache_control"));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::chat_client::complete;
+use crate::conversation::{ConversationContext, Message, MessageSource};
+use crate::message_printer::{MessageType, print_message, print_reply};
+use crate::spinner::run_with_spinner;
+use crate::tc_config::{ConfigTC, CriticKeep};
+use crate::templates::prompt;
+use std::error::Error;
+
+const REVISED_HEADING: &str = "## Revised Answer";
+
+/// Has `critic_model` review the assistant reply at the end of `ctx`, shows
+/// the critique and revision, then stores whatever `critic_keep` asks for.
+pub(crate) async fn critique_last_reply(
+    ctx: &mut ConversationContext,
+    critic_model: &str,
+    config: &ConfigTC,
+) -> Result<(), Box<dyn Error>> {
+    let Some(draft) = ctx.input.last().filter(|m| m.role == "assistant").cloned() else {
+        return Ok(());
+    };
+
+    let mut transcript = String::new();
+    for msg in ctx.input.iter().take(ctx.input.len() - 1) {
+        if msg.role != "developer" {
+            transcript.push_str(&format!("{}:\n{}\n\n", msg.role, msg.content));
+        }
+    }
+
+    let mut review = ConversationContext::new(critic_model, false);
+    review
+        .input
+        .push(Message::new("developer", prompt("critic_prompt")?));
+    review.input.push(Message::new(
+        "user",
+        format!(
+            "Conversation:\n\n{}\n:::\n\nDraft answer:\n\n{}",
+            transcript, draft.content
+        ),
+    ));
+
+    let response = run_with_spinner(complete(&review)).await?;
+
+    let (critique, revised) = split_review(&response.text, &draft.content);
+
+    print_message(
+        &format!("Critique from {}", critic_model),
+        MessageType::System,
+        config,
+    );
+    print_reply(&critique, config);
+    print_message("Revised answer", MessageType::System, config);
+    print_reply(&revised, config);
+
+    let stored = match config.critic_keep {
+        CriticKeep::Draft => return Ok(()),
+        CriticKeep::Revised => revised,
+        CriticKeep::All => format!(
+            "{}\n\n---\nCritique from {}:\n\n{}\n\n---\nRevised answer:\n\n{}",
+            draft.content, critic_model, critique, revised
+        ),
+    };
+    if let Some(last) = ctx.input.last_mut() {
+        last.content = stored;
+        last.source = MessageSource::Critic;
+    }
+    Ok(())
+}
+
+/// The critic is asked for a critique followed by a revised answer under
+/// `REVISED_HEADING`. Without that heading the draft stands as the revision.
+fn split_review(review: &str, draft: &str) -> (String, String) {
+    match review.split_once(REVISED_HEADING) {
+        Some((critique, revised)) => (
+            critique
+                .trim()
+                .trim_start_matches("## Critique")
+                .trim()
+                .to_string(),
+            revised.trim().to_string(),
+        ),
+        None => (review.trim().to_string(), draft.to_string()),
+    }
+}
@@ -0,0 +1,327 @@
+use crate::tc_config::get_config;
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use dirs::data_dir;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{ErrorKind, Write, stdout};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+// Encrypted files are MAGIC, a salt for Argon2, the nonce, then the
+// XChaCha20-Poly1305 ciphertext of what would otherwise be written.
+const MAGIC: &[u8] = b"TCENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+// Sealed with the passphrase next to the sessions, so a mistyped one is
+// caught before anything is encrypted with it.
+const VERIFIER: &[u8] = b"tc storage passphrase";
+
+/// Set to skip the passphrase prompt, e.g. in scripts.
+pub(crate) const PASSPHRASE_ENV: &str = "TC_PASSPHRASE";
+
+static PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+static VERIFIED: AtomicBool = AtomicBool::new(false);
+
+// Deriving a key is deliberately slow, so files written by one run share a
+// salt and each salt is only derived once.
+static WRITE_SALT: LazyLock<[u8; SALT_LEN]> = LazyLock::new(|| {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+});
+static KEYS: LazyLock<Mutex<HashMap<[u8; SALT_LEN], [u8; 32]>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether new conversation, session, index and history files get encrypted.
+pub(crate) fn encryption_enabled() -> bool {
+    get_config().is_ok_and(|c| c.encrypt_storage)
+}
+
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Reads a file that may be encrypted, asking for the passphrase if needed.
+pub(crate) fn read_protected(path: &Path) -> Result<String, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    let bytes = if is_encrypted(&bytes) {
+        decrypt(&bytes)?
+    } else {
+        bytes
+    };
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Writes `contents`, encrypted when `encrypt_storage` is on.
+pub(crate) fn write_protected(path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+    if encryption_enabled() {
+        fs::write(path, encrypt(contents.as_bytes())?)?;
+    } else {
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+/// Encrypts the plaintext `.json` files directly in `dirs` and any extra
+/// `files` in place. Returns how many were encrypted and how many already
+/// were.
+pub(crate) fn encrypt_existing(
+    dirs: &[PathBuf],
+    files: &[PathBuf],
+) -> Result<(usize, usize), Box<dyn Error>> {
+    let mut paths = files.to_vec();
+    for dir in dirs {
+        if let Ok(entries) = fs::read_dir(dir) {
+            paths.extend(
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|e| e == "json")),
+            );
+        }
+    }
+
+    let (mut encrypted, mut skipped) = (0, 0);
+    for path in paths.iter().filter(|p| p.is_file()) {
+        let bytes = fs::read(path)?;
+        if is_encrypted(&bytes) {
+            skipped += 1;
+            continue;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, encrypt(&bytes)?)?;
+        fs::rename(tmp, path)?;
+        encrypted += 1;
+    }
+    Ok((encrypted, skipped))
+}
+
+pub(crate) fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    check_passphrase()?;
+    let salt = *WRITE_SALT;
+    seal(&key_for(&salt)?, &salt, plaintext)
+}
+
+fn get_verifier_path() -> PathBuf {
+    match data_dir() {
+        Some(dir) => dir.join("tc").join("passphrase_check"),
+        None => PathBuf::from("passphrase_check"),
+    }
+}
+
+/// Checks the passphrase against the stored verifier once per run, or
+/// stores one if there is none yet.
+pub(crate) fn check_passphrase() -> Result<(), Box<dyn Error>> {
+    if VERIFIED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    check_verifier(&get_verifier_path())?;
+    VERIFIED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+fn check_verifier(path: &Path) -> Result<(), Box<dyn Error>> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            if decrypt(&bytes).ok().as_deref() != Some(VERIFIER) {
+                forget_passphrase();
+                return Err(
+                    "Wrong passphrase: it does not match the one existing files use.".into(),
+                );
+            }
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let salt = *WRITE_SALT;
+            fs::write(path, seal(&key_for(&salt)?, &salt, VERIFIER)?)?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+fn decrypt(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let salt: [u8; SALT_LEN] = bytes
+        .get(MAGIC.len()..MAGIC.len() + SALT_LEN)
+        .and_then(|s| s.try_into().ok())
+        .ok_or("Encrypted file is truncated")?;
+    let result = open(&key_for(&salt)?, bytes);
+    if result.is_err() {
+        // Most likely a mistyped passphrase, so ask again next time.
+        forget_passphrase();
+    }
+    result
+}
+
+fn seal(
+    key: &[u8; 32],
+    salt: &[u8; SALT_LEN],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed")?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn open(key: &[u8; 32], bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let header = MAGIC.len() + SALT_LEN;
+    let nonce = bytes
+        .get(header..header + NONCE_LEN)
+        .ok_or("Encrypted file is truncated")?;
+    let cipher = XChaCha20Poly1305::new(key.into());
+    Ok(cipher
+        .decrypt(XNonce::from_slice(nonce), &bytes[header + NONCE_LEN..])
+        .map_err(|_| "Could not decrypt. Wrong passphrase or a damaged file.")?)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], Box<dyn Error>> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn key_for(salt: &[u8; SALT_LEN]) -> Result<[u8; 32], Box<dyn Error>> {
+    if let Some(key) = KEYS.lock().map_err(|_| "Key cache poisoned")?.get(salt) {
+        return Ok(*key);
+    }
+    let key = derive_key(&passphrase(false)?, salt)?;
+    KEYS.lock()
+        .map_err(|_| "Key cache poisoned")?
+        .insert(*salt, key);
+    Ok(key)
+}
+
+/// The passphrase for this run, from `TC_PASSPHRASE` or asked for once.
+/// With `confirm` it is asked for twice, for setting up encryption.
+pub(crate) fn passphrase(confirm: bool) -> Result<String, Box<dyn Error>> {
+    let mut cached = PASSPHRASE.lock().map_err(|_| "Passphrase lock poisoned")?;
+    if let Some(passphrase) = cached.as_ref() {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = match env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => passphrase,
+        _ => {
+            let first = read_hidden("Storage passphrase: ")?;
+            if confirm && read_hidden("Repeat passphrase: ")? != first {
+                return Err("Passphrases do not match".into());
+            }
+            first
+        }
+    };
+    if passphrase.is_empty() {
+        return Err("The passphrase cannot be empty".into());
+    }
+    *cached = Some(passphrase.clone());
+    Ok(passphrase)
+}
+
+fn forget_passphrase() {
+    if let Ok(mut cached) = PASSPHRASE.lock() {
+        *cached = None;
+    }
+    VERIFIED.store(false, Ordering::Relaxed);
+    if let Ok(mut keys) = KEYS.lock() {
+        keys.clear();
+    }
+}
+
+/// Reads a line without echoing it.
+fn read_hidden(prompt: &str) -> Result<String, Box<dyn Error>> {
+    print!("{}", prompt);
+    stdout().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let mut input = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err("Cancelled".into());
+                }
+                KeyCode::Esc => break Err("Cancelled".into()),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(Box::<dyn Error>::from(e)),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    println!();
+    result.map(|_| input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() -> Result<(), Box<dyn Error>> {
+        let salt = [7; SALT_LEN];
+        let key = derive_key("correct horse", &salt)?;
+        let sealed = seal(&key, &salt, b"{\"model\":\"gpt-4o\"}")?;
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(5).any(|w| w == b"model"));
+        assert_eq!(open(&key, &sealed)?, b"{\"model\":\"gpt-4o\"}");
+
+        let wrong = derive_key("wrong horse", &salt)?;
+        assert!(open(&wrong, &sealed).is_err());
+        assert!(open(&key, &sealed[..MAGIC.len() + 4]).is_err());
+        Ok(())
+    }
+
+    fn set_passphrase(passphrase: &str) -> Result<(), Box<dyn Error>> {
+        forget_passphrase();
+        *PASSPHRASE.lock().map_err(|_| "poisoned")? = Some(passphrase.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verifier_rejects_a_different_passphrase() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("tc_verifier_{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        set_passphrase("correct horse")?;
+        check_verifier(&path)?;
+        assert!(is_encrypted(&fs::read(&path)?));
+        check_verifier(&path)?;
+
+        set_passphrase("corect horse")?;
+        assert!(check_verifier(&path).is_err());
+        // The wrong passphrase is forgotten so it is asked for again.
+        assert!(PASSPHRASE.lock().map_err(|_| "poisoned")?.is_none());
+
+        set_passphrase("correct horse")?;
+        check_verifier(&path)?;
+        forget_passphrase();
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
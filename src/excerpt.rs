@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Part of a file: `path:10-80` for lines 10 to 80, or `path#name` for a
+/// Rust item such as a function, struct, trait or impl (`Type::method`
+/// finds a method inside `impl Type`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    Lines { start: usize, end: usize },
+    Symbol(String),
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::Lines { start, end } => write!(f, ":{}-{}", start, end),
+            Selection::Symbol(name) => write!(f, "#{}", name),
+        }
+    }
+}
+
+impl Selection {
+    /// Splits `src/run.rs:10-80` or `src/run.rs#as_repl` into the path and
+    /// the selection. Anything else is not a reference.
+    pub(crate) fn parse(arg: &str) -> Option<(&str, Selection)> {
+        if let Some((path, name)) = arg.rsplit_once('#')
+            && !path.is_empty()
+            && !name.is_empty()
+            && name.split("::").all(is_identifier)
+        {
+            return Some((path, Selection::Symbol(name.to_string())));
+        }
+        let (path, range) = arg.rsplit_once(':')?;
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+        if path.is_empty() || start == 0 || end < start {
+            return None;
+        }
+        Some((path, Selection::Lines { start, end }))
+    }
+
+    /// The selected part of `content`, read from `path`.
+    pub(crate) fn extract(&self, path: &str, content: &str) -> Result<String, String> {
+        match self {
+            Selection::Lines { start, end } => {
+                let lines: Vec<&str> = content.lines().collect();
+                if *start > lines.len() {
+                    return Err(format!("{} has only {} lines", path, lines.len()));
+                }
+                Ok(lines[start - 1..(*end).min(lines.len())].join("\n"))
+            }
+            Selection::Symbol(name) => {
+                if Path::new(path).extension().is_none_or(|e| e != "rs") {
+                    return Err(format!(
+                        "{}: symbol references only work in Rust files",
+                        path
+                    ));
+                }
+                let lines: Vec<&str> = content.lines().collect();
+                let (start, end) = find_symbol(&lines, name)
+                    .ok_or_else(|| format!("{}: no item named {}", path, name))?;
+                Ok(lines[start..=end].join("\n"))
+            }
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// First and last line (0-based) of the item `name`, including its doc
+/// comments and attributes. `Type::method` looks inside `impl Type` blocks.
+fn find_symbol(lines: &[&str], name: &str) -> Option<(usize, usize)> {
+    if let Some((owner, method)) = name.rsplit_once("::") {
+        return (0..lines.len())
+            .filter(|&i| impl_target(lines[i]).is_some_and(|t| t == owner))
+            .find_map(|i| {
+                let end = item_end(lines, i)?;
+                let (start, end) = find_symbol(&lines[i + 1..end], method)?;
+                Some((i + 1 + start, i + 1 + end))
+            });
+    }
+
+    let start = (0..lines.len()).find(|&i| {
+        item_name(lines[i]).is_some_and(|n| n == name)
+            || impl_target(lines[i]).is_some_and(|t| t == name)
+    })?;
+    let end = item_end(lines, start)?;
+    Some((doc_start(lines, start), end))
+}
+
+/// The name declared by `line`, for `fn`, `struct`, `enum`, `trait`, `type`,
+/// `mod`, `const`, `static`, `union` and `macro_rules!` items.
+fn item_name(line: &str) -> Option<&str> {
+    let rest = strip_qualifiers(line.trim_start());
+    let rest = [
+        "fn ",
+        "struct ",
+        "enum ",
+        "trait ",
+        "type ",
+        "mod ",
+        "const ",
+        "static ",
+        "union ",
+        "macro_rules! ",
+    ]
+    .iter()
+    .find_map(|keyword| rest.strip_prefix(keyword))?;
+    let rest = rest
+        .trim_start()
+        .strip_prefix("mut ")
+        .unwrap_or(rest)
+        .trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    Some(&rest[..end]).filter(|n| !n.is_empty())
+}
+
+/// The type an `impl` line is for: `Foo` in `impl<T> Display for Foo<T> {`.
+fn impl_target(line: &str) -> Option<&str> {
+    let mut rest = strip_qualifiers(line.trim_start()).strip_prefix("impl")?;
+    if rest.starts_with('<') {
+        let mut depth = 0;
+        let close = rest.char_indices().find_map(|(i, c)| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(i)
+        })?;
+        rest = rest[close + 1..].trim_start();
+    } else if rest.len() == rest.trim_start().len() {
+        // `implements`, not `impl`.
+        return None;
+    }
+    let head = rest.split('{').next()?.split(" where").next()?;
+    let target = head.rsplit(" for ").next()?.split('<').next()?.trim();
+    target.rsplit("::").next().filter(|t| !t.is_empty())
+}
+
+fn strip_qualifiers(mut line: &str) -> &str {
+    loop {
+        let before = line;
+        if let Some(rest) = line.strip_prefix("pub ") {
+            line = rest;
+        } else if let Some(scoped) = line.strip_prefix("pub(") {
+            line = scoped.split_once(')').map_or(line, |(_, rest)| rest);
+        }
+        for qualifier in ["async ", "const fn", "unsafe ", "default ", "extern \"C\" "] {
+            if let Some(rest) = line.strip_prefix(qualifier) {
+                // `const fn` keeps the `fn`.
+                line = if qualifier == "const fn" {
+                    &line["const ".len()..]
+                } else {
+                    rest
+                };
+            }
+        }
+        line = line.trim_start();
+        if line == before {
+            return line;
+        }
+    }
+}
+
+/// Moves `start` up over the doc comments and attributes above an item.
+fn doc_start(lines: &[&str], start: usize) -> usize {
+    let mut first = start;
+    while first > 0 {
+        let above = lines[first - 1].trim_start();
+        if above.starts_with("///") || above.starts_with("#[") {
+            first -= 1;
+        } else {
+            break;
+        }
+    }
+    first
+}
+
+/// The line an item starting at `start` ends on: where its braces close, or
+/// the `;` of an item without a body.
+fn item_end(lines: &[&str], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut opened = false;
+    let mut in_block_comment = false;
+    let mut in_string = false;
+
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let chars: Vec<char> = line.chars().collect();
+        let mut j = 0;
+        while j < chars.len() {
+            let c = chars[j];
+            let next = chars.get(j + 1).copied();
+            if in_block_comment {
+                if c == '*' && next == Some('/') {
+                    in_block_comment = false;
+                    j += 1;
+                }
+            } else if in_string {
+                match c {
+                    '\\' => j += 1,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match c {
+                    '/' if next == Some('/') => break,
+                    '/' if next == Some('*') => {
+                        in_block_comment = true;
+                        j += 1;
+                    }
+                    '"' => in_string = true,
+                    // A char literal such as '{' or '\n', not a lifetime.
+                    '\'' if next == Some('\\') => {
+                        j += chars
+                            .get(j + 3..)
+                            .and_then(|rest| rest.iter().position(|&c| c == '\''))
+                            .map_or(1, |p| p + 3);
+                    }
+                    '\'' if chars.get(j + 2) == Some(&'\'') => j += 2,
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' => {
+                        depth = depth.saturating_sub(1);
+                        if opened && depth == 0 {
+                            return Some(i);
+                        }
+                    }
+                    ';' if !opened => return Some(i),
+                    _ => {}
+                }
+            }
+            j += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"use std::fmt;
+
+/// A point.
+#[derive(Debug)]
+pub(crate) struct Point {
+    x: i32,
+}
+
+impl Point {
+    pub const fn new() -> Self {
+        let brace = '{';
+        let text = "}}";
+        Self { x: 0 }
+    }
+
+    fn len(&self) -> usize { 0 }
+}
+
+impl<T> fmt::Display for Wrapper<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "w")
+    }
+}
+
+type Alias = Point;
+"#;
+
+    fn symbol(name: &str) -> Result<String, String> {
+        Selection::Symbol(name.to_string()).extract("point.rs", SOURCE)
+    }
+
+    #[test]
+    fn test_parse_references() {
+        assert_eq!(
+            Selection::parse("src/run.rs:10-80"),
+            Some(("src/run.rs", Selection::Lines { start: 10, end: 80 }))
+        );
+        assert_eq!(
+            Selection::parse("src/run.rs#Session::load"),
+            Some(("src/run.rs", Selection::Symbol("Session::load".into())))
+        );
+        assert_eq!(Selection::parse("src/run.rs"), None);
+        assert_eq!(Selection::parse("src/run.rs:80-10"), None);
+        assert_eq!(Selection::parse("C:\\src\\run.rs"), None);
+    }
+
+    #[test]
+    fn test_extract_lines_and_symbols() {
+        let lines = Selection::Lines { start: 3, end: 4 }.extract("point.rs", SOURCE);
+        assert_eq!(lines.as_deref(), Ok("/// A point.\n#[derive(Debug)]"));
+        assert!(
+            Selection::Lines {
+                start: 99,
+                end: 100
+            }
+            .extract("point.rs", SOURCE)
+            .is_err()
+        );
+
+        let point = symbol("Point").unwrap_or_default();
+        assert!(point.starts_with("/// A point."));
+        assert!(point.ends_with("    x: i32,\n}"));
+
+        // Braces in char and string literals do not end the function early.
+        let new = symbol("Point::new").unwrap_or_default();
+        assert!(new.starts_with("    pub const fn new()"));
+        assert!(new.ends_with("        Self { x: 0 }\n    }"));
+
+        assert_eq!(
+            symbol("Point::len").as_deref(),
+            Ok("    fn len(&self) -> usize { 0 }")
+        );
+        assert!(symbol("Wrapper").unwrap_or_default().contains("write!"));
+        assert_eq!(symbol("Alias").as_deref(), Ok("type Alias = Point;"));
+        assert!(symbol("missing").is_err());
+        assert!(
+            Selection::Symbol("main".into())
+                .extract("notes.md", "fn main() {}")
+                .is_err()
+        );
+    }
+}
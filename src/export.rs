@@ -0,0 +1,338 @@
+use crate::conversation::{ConversationContext, Message, MessageSource};
+use crate::storage::read_context;
+use crate::tc_config::Theme;
+use crate::utils::{format_timestamp, now_secs};
+use pulldown_cmark::{Event, Options, Parser, html};
+use serde::Serialize;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            "jsonl" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+pub(crate) fn export(
+    ctx: &ConversationContext,
+    title: &str,
+    format: ExportFormat,
+    theme: &Theme,
+) -> Result<String, Box<dyn Error>> {
+    Ok(match format {
+        ExportFormat::Markdown => to_markdown(ctx, title),
+        ExportFormat::Html => to_html(ctx, title, theme),
+        ExportFormat::Jsonl => to_jsonl_line(ctx)? + "\n",
+    })
+}
+
+/// A title for exports, taken from the first thing the user asked.
+pub(crate) fn default_title(ctx: &ConversationContext) -> String {
+    ctx.input
+        .iter()
+        .find(|m| m.role == "user" && m.source == MessageSource::Typed)
+        .map(|m| m.preview(60))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Conversation".to_string())
+}
+
+/// Converts every conversation in `source_dir`. Markdown and HTML get one
+/// file each, JSONL is written as a single dataset with one line per conversation.
+pub(crate) fn export_dir(
+    source_dir: &Path,
+    out_dir: &Path,
+    format: ExportFormat,
+    theme: &Theme,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut paths: Vec<_> = fs::read_dir(source_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+
+    fs::create_dir_all(out_dir)?;
+    let mut written = Vec::new();
+    let mut dataset = String::new();
+
+    for path in paths {
+        let ctx = match read_context(&path) {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                eprintln!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if format == ExportFormat::Jsonl {
+            dataset.push_str(&to_jsonl_line(&ctx)?);
+            dataset.push('\n');
+            continue;
+        }
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let out = out_dir.join(format!("{}.{}", stem, format.extension()));
+        fs::write(&out, export(&ctx, &stem, format, theme)?)?;
+        written.push(out.display().to_string());
+    }
+
+    if format == ExportFormat::Jsonl {
+        let out = out_dir.join("conversations.jsonl");
+        fs::write(&out, dataset)?;
+        written.push(out.display().to_string());
+    }
+    Ok(written)
+}
+
+fn role_title(role: &str) -> &str {
+    match role {
+        "developer" | "system" => "System",
+        "assistant" => "Assistant",
+        _ => "User",
+    }
+}
+
+/// Files pulled in with `:gf` or `:readme` are stored as `path\n\n:::\n\ncontent`.
+fn split_file_message(msg: &Message) -> Option<(&str, &str)> {
+    if !msg.source.is_loaded() {
+        return None;
+    }
+    msg.content.split_once("\n\n:::\n\n")
+}
+
+fn to_markdown(ctx: &ConversationContext, title: &str) -> String {
+    let mut out = format!(
+        "# {}\n\n*Model: {} · Exported {} UTC*\n",
+        title,
+        ctx.model,
+        format_timestamp(now_secs())
+    );
+
+    for msg in &ctx.input {
+        match split_file_message(msg) {
+            Some((path, content)) => {
+                let fence = if content.contains("```") {
+                    "~~~~"
+                } else {
+                    "```"
+                };
+                let lang = Path::new(path)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("");
+                out.push_str(&format!(
+                    "\n## {} · `{}`\n\n{}{}\n{}\n{}\n",
+                    role_title(&msg.role),
+                    path,
+                    fence,
+                    lang,
+                    content.trim_end(),
+                    fence
+                ));
+            }
+            None => out.push_str(&format!(
+                "\n## {}\n\n{}\n",
+                role_title(&msg.role),
+                msg.content.trim_end()
+            )),
+        }
+    }
+    out
+}
+
+fn css_color(name: &str) -> String {
+    match name {
+        "dark_yellow" => "olive".to_string(),
+        "light_grey" => "lightgray".to_string(),
+        "dark_grey" => "dimgray".to_string(),
+        other => other.replace('_', ""),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn to_html(ctx: &ConversationContext, title: &str, theme: &Theme) -> String {
+    let mut body = String::new();
+    for msg in &ctx.input {
+        let class = match msg.role.as_str() {
+            "developer" | "system" => "system",
+            "assistant" => "assistant",
+            _ => "user",
+        };
+        let (heading, rendered) = match split_file_message(msg) {
+            Some((path, content)) => (
+                format!(
+                    "{} · <code>{}</code>",
+                    role_title(&msg.role),
+                    escape_html(path)
+                ),
+                format!("<pre><code>{}</code></pre>", escape_html(content)),
+            ),
+            None => {
+                // Raw HTML in a message is shown as text, never run.
+                let events =
+                    Parser::new_ext(&msg.content, Options::all()).map(|event| match event {
+                        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+                        event => event,
+                    });
+                let mut rendered = String::new();
+                html::push_html(&mut rendered, events);
+                (role_title(&msg.role).to_string(), rendered)
+            }
+        };
+        body.push_str(&format!(
+            "<section class=\"message {}\">\n<h2>{}</h2>\n{}</section>\n",
+            class, heading, rendered
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; background: #1e1f22; color: #dcdcdc; line-height: 1.5; }}
+header p {{ color: #8c8c8c; }}
+.message {{ border-left: 4px solid; border-radius: 6px; padding: 0.25rem 1rem; margin: 1rem 0; background: #26282c; }}
+.message h2 {{ font-size: 0.9rem; text-transform: uppercase; letter-spacing: 0.05em; }}
+.system {{ border-color: {system}; }} .system h2 {{ color: {system}; }}
+.user {{ border-color: {user}; }} .user h2 {{ color: {user}; }}
+.assistant {{ border-color: {assistant}; }} .assistant h2 {{ color: {assistant}; }}
+pre {{ background: #111214; padding: 0.75rem; border-radius: 6px; overflow-x: auto; }}
+code {{ font-family: "SF Mono", Menlo, Consolas, monospace; font-size: 0.9em; }}
+:not(pre) > code {{ background: #111214; padding: 0.1em 0.3em; border-radius: 4px; }}
+table {{ border-collapse: collapse; }} td, th {{ border: 1px solid #444; padding: 0.25rem 0.5rem; }}
+</style>
+</head>
+<body>
+<header>
+<h1>{title}</h1>
+<p>Model: {model} · Exported {date} UTC</p>
+</header>
+{body}</body>
+</html>
+"#,
+        title = escape_html(title),
+        model = escape_html(&ctx.model),
+        date = format_timestamp(now_secs()),
+        system = css_color(&theme.system_color),
+        user = css_color(&theme.user_color),
+        assistant = css_color(&theme.assistant_color),
+        body = body
+    )
+}
+
+#[derive(Serialize)]
+struct TrainingExample<'a> {
+    messages: Vec<TrainingMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct TrainingMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+/// One conversation in the chat fine-tuning format, `{"messages": [...]}`.
+fn to_jsonl_line(ctx: &ConversationContext) -> Result<String, Box<dyn Error>> {
+    let example = TrainingExample {
+        messages: ctx
+            .input
+            .iter()
+            .map(|m| TrainingMessage {
+                role: match m.role.as_str() {
+                    "developer" => "system",
+                    role => role,
+                },
+                content: &m.content,
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string(&example)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tc_config::default_theme;
+
+    fn conversation() -> ConversationContext {
+        let mut ctx = ConversationContext::new("gpt-4o", false);
+        ctx.input.push(Message::new("developer", "Be brief."));
+        ctx.input.push(Message::new(
+            "user",
+            "Is <script>alert(1)</script> safe? <img src=x onerror=alert(2)>",
+        ));
+        ctx.input.push(Message::from_file(
+            "src/main.rs",
+            "fn main() {}",
+            MessageSource::Gf,
+        ));
+        ctx.input.push(Message::new("assistant", "**No.**"));
+        ctx
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = to_markdown(&conversation(), "Safety");
+        assert!(markdown.starts_with("# Safety\n\n*Model: gpt-4o"));
+        assert!(markdown.contains("\n## System\n\nBe brief.\n"));
+        assert!(markdown.contains("\n## User · `src/main.rs`\n\n```rs\nfn main() {}\n```\n"));
+        assert!(markdown.ends_with("\n## Assistant\n\n**No.**\n"));
+    }
+
+    #[test]
+    fn test_to_html_escapes_raw_html() {
+        let html = to_html(&conversation(), "<b>Safety</b>", &default_theme());
+        assert!(html.contains("<title>&lt;b&gt;Safety&lt;/b&gt;</title>"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("<pre><code>fn main() {}</code></pre>"));
+        assert!(html.contains("<strong>No.</strong>"));
+    }
+
+    #[test]
+    fn test_to_jsonl_line() -> Result<(), Box<dyn Error>> {
+        let line = to_jsonl_line(&conversation())?;
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line)?;
+        let messages = value["messages"].as_array().cloned().unwrap_or_default();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages[0]["content"], "Be brief.");
+        assert_eq!(messages[3]["role"], "assistant");
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::utils::confirm_action;
+use std::error::Error;
+use std::process::Command;
+
+/// The part of a diff for one file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FileDiff {
+    pub(crate) path: String,
+    pub(crate) patch: String,
+}
+
+impl FileDiff {
+    /// Lines added and removed. The `---` and `+++` file names come before
+    /// the first hunk; after it, such lines are content.
+    pub(crate) fn stat(&self) -> (usize, usize) {
+        let lines = self.patch.lines().skip_while(|l| !l.starts_with("@@"));
+        lines.fold((0, 0), |(added, removed), line| match line.chars().next() {
+            Some('+') => (added + 1, removed),
+            Some('-') => (added, removed + 1),
+            _ => (added, removed),
+        })
+    }
+}
+
+/// Runs git with `args` in the current directory and returns its output.
+pub(crate) fn git(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `git diff` with `args`, such as `--staged` or `main..HEAD`, split by file.
+pub(crate) fn diff(args: &[&str]) -> Result<Vec<FileDiff>, Box<dyn Error>> {
+    let mut full = vec!["diff", "--no-color", "--no-ext-diff"];
+    full.extend_from_slice(args);
+    Ok(split_diff(&git(&full)?))
+}
+
+fn split_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            // `a/old b/new`; the new name is what the file is called now.
+            let path = header
+                .rsplit_once(" b/")
+                .map_or(header, |(_, path)| path)
+                .to_string();
+            files.push(FileDiff {
+                path,
+                patch: String::new(),
+            });
+        }
+        if let Some(file) = files.last_mut() {
+            file.patch.push_str(line);
+            file.patch.push('\n');
+        }
+    }
+    files
+}
+
+/// The diff as markdown, one section per file, for putting into context.
+pub(crate) fn format_diff(files: &[FileDiff]) -> String {
+    files
+        .iter()
+        .map(|file| format!("### {}\n\n```diff\n{}```", file.path, file.patch))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Whether a diff of `files` coming to ~`tokens` may be sent, asking first
+/// when it is over `budget` as `:gf` does.
+pub(crate) fn confirm_diff_size(files: usize, tokens: usize, budget: usize) -> bool {
+    tokens <= budget
+        || confirm_action(&format!(
+            "\nThe diff of {} files comes to ~{} tokens, over the budget of {}. Use it anyway? (y/n): ",
+            files, tokens, budget
+        ))
+}
+
+/// A line per file with lines added and removed.
+pub(crate) fn diff_summary(files: &[FileDiff]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            let (added, removed) = file.stat();
+            format!(
+                "{:>6} {:>6}  {}",
+                format!("+{}", added),
+                format!("-{}", removed),
+                file.path
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/run.rs b/src/run.rs
+index 1111111..2222222 100644
+--- a/src/run.rs
++++ b/src/run.rs
+@@ -1,2 +1,2 @@
+-use std::old;
++use std::new;
+ fn main() {}
+@@ -9,2 +9,2 @@
+--- a SQL comment
++++ an upvote
+diff --git a/old.md b/docs/new.md
+similarity index 90%
+rename from old.md
+rename to docs/new.md
+";
+
+    #[test]
+    fn test_split_diff() {
+        let files = split_diff(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/run.rs");
+        assert_eq!(files[0].stat(), (2, 2));
+        assert!(files[0].patch.ends_with("+++ an upvote\n"));
+        assert_eq!(files[1].path, "docs/new.md");
+        assert_eq!(files[1].stat(), (0, 0));
+
+        let formatted = format_diff(&files);
+        assert!(formatted.starts_with("### src/run.rs\n\n```diff\ndiff --git"));
+        assert!(split_diff("").is_empty());
+    }
+}
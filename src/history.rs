@@ -0,0 +1,52 @@
+use crate::crypto::{read_protected, write_protected};
+use dirs::data_dir;
+use linefeed::{DefaultTerminal, Interface};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+// Older lines are dropped when the history is saved.
+const MAX_LINES: usize = 1000;
+
+/// Where lines typed at the prompt are kept between runs. Encrypted like
+/// conversations when `encrypt_storage` is on.
+pub(crate) fn get_history_path() -> PathBuf {
+    match data_dir() {
+        Some(dir) => dir.join("tc").join("history"),
+        None => PathBuf::from("history"),
+    }
+}
+
+/// Adds the saved lines to `interface`. Returns false when they could not
+/// be read, so that saving does not overwrite them.
+pub(crate) fn load_history(interface: &Interface<DefaultTerminal>) -> bool {
+    let path = get_history_path();
+    if !path.exists() {
+        return true;
+    }
+    match read_protected(&path) {
+        Ok(text) => {
+            for line in text.lines() {
+                interface.add_history(line.to_string());
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("Could not read history {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+pub(crate) fn save_history(interface: &Interface<DefaultTerminal>) -> Result<(), Box<dyn Error>> {
+    let writer = interface.lock_writer_append()?;
+    let lines: Vec<&str> = writer.history().collect();
+    let mut text = lines[lines.len().saturating_sub(MAX_LINES)..].join("\n");
+    text.push('\n');
+
+    let path = get_history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_protected(&path, &text)
+}
@@ -0,0 +1,409 @@
+use crate::conversation::{ConversationContext, Message, MessageSource};
+use crate::session::Session;
+use crate::storage::ConversationMeta;
+use crate::tc_config::{ConfigTC, read_saved_config};
+use crate::utils::{now_secs, parse_timestamp};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// Used when neither the export nor the config names a usable model.
+const CHATGPT_FALLBACK_MODEL: &str = "gpt-4o";
+const CLAUDE_FALLBACK_MODEL: &str = "claude-sonnet-4-5";
+
+/// One conversation from ChatGPT's `conversations.json`. Messages form a
+/// tree in `mapping`; the thread shown in the UI ends at `current_node`.
+#[derive(Deserialize, Debug)]
+struct ChatGptConversation {
+    #[serde(default, alias = "conversation_id")]
+    id: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    create_time: Option<f64>,
+    #[serde(default)]
+    update_time: Option<f64>,
+    #[serde(default)]
+    mapping: HashMap<String, ChatGptNode>,
+    #[serde(default)]
+    current_node: Option<String>,
+    #[serde(default)]
+    default_model_slug: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatGptNode {
+    #[serde(default)]
+    message: Option<ChatGptMessage>,
+    #[serde(default)]
+    parent: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatGptMessage {
+    author: ChatGptAuthor,
+    #[serde(default)]
+    content: ChatGptContent,
+    #[serde(default)]
+    metadata: Value,
+    #[serde(default)]
+    create_time: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatGptAuthor {
+    role: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ChatGptContent {
+    #[serde(default)]
+    parts: Vec<Value>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// One conversation from the `conversations.json` in Claude's export archive.
+#[derive(Deserialize, Debug)]
+struct ClaudeConversation {
+    uuid: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+    #[serde(default)]
+    chat_messages: Vec<ClaudeMessage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClaudeMessage {
+    sender: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    content: Vec<ClaudeBlock>,
+    #[serde(default)]
+    attachments: Vec<ClaudeAttachment>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClaudeBlock {
+    #[serde(rename = "type")]
+    type_field: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ClaudeAttachment {
+    #[serde(default)]
+    file_name: String,
+    #[serde(default)]
+    extracted_content: String,
+}
+
+/// Converts a ChatGPT or Claude export into sessions and writes them to the
+/// sessions dir. `path` is the `conversations.json` file or the unzipped
+/// export folder holding it. Importing the same export twice overwrites
+/// the earlier copies instead of duplicating them.
+pub(crate) fn import(path: &Path) -> Result<Vec<Session>, Box<dyn Error>> {
+    let path = if path.is_dir() {
+        path.join("conversations.json")
+    } else {
+        path.to_path_buf()
+    };
+    let as_str = fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let config = read_saved_config().and_then(Result::ok);
+
+    let sessions = parse_export(&as_str, config.as_ref())?;
+    for session in &sessions {
+        session.write()?;
+    }
+    Ok(sessions)
+}
+
+fn parse_export(as_str: &str, config: Option<&ConfigTC>) -> Result<Vec<Session>, Box<dyn Error>> {
+    let raw: Vec<Value> = serde_json::from_str(as_str)?;
+    let Some(first) = raw.first() else {
+        return Ok(vec![]);
+    };
+
+    if first.get("mapping").is_some() {
+        let conversations: Vec<ChatGptConversation> = serde_json::from_value(Value::Array(raw))?;
+        Ok(conversations
+            .iter()
+            .filter_map(|c| from_chatgpt(c, config))
+            .collect())
+    } else if first.get("chat_messages").is_some() {
+        let conversations: Vec<ClaudeConversation> = serde_json::from_value(Value::Array(raw))?;
+        Ok(conversations
+            .iter()
+            .filter_map(|c| from_claude(c, config))
+            .collect())
+    } else {
+        Err("Unrecognized export. Expected ChatGPT or Claude conversations.json.".into())
+    }
+}
+
+fn from_chatgpt(conversation: &ChatGptConversation, config: Option<&ConfigTC>) -> Option<Session> {
+    // Walk up from the last node, which skips branches abandoned by edits
+    // and regenerations, then put the thread back in order.
+    let mut thread = Vec::new();
+    let mut node_id = conversation.current_node.clone();
+    while let Some(id) = node_id {
+        let Some(node) = conversation.mapping.get(&id) else {
+            break;
+        };
+        if let Some(message) = &node.message {
+            thread.push(message);
+        }
+        node_id = node.parent.clone();
+    }
+    thread.reverse();
+
+    let mut model_slug = conversation.default_model_slug.clone();
+    let mut input = Vec::new();
+    for message in thread {
+        let hidden = message
+            .metadata
+            .get("is_visually_hidden_from_conversation")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let role = match message.author.role.as_str() {
+            "system" => "developer",
+            "user" => "user",
+            "assistant" => "assistant",
+            // Tool calls and browsing results have no counterpart in tc.
+            _ => continue,
+        };
+
+        let text = match &message.content.text {
+            Some(text) => text.clone(),
+            None => message
+                .content
+                .parts
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+        if hidden || text.trim().is_empty() {
+            continue;
+        }
+
+        if role == "assistant"
+            && let Some(slug) = message.metadata.get("model_slug").and_then(Value::as_str)
+        {
+            model_slug = Some(slug.to_string());
+        }
+        input.push(Message {
+            source: imported_source(role),
+            model: (role == "assistant").then(|| model_slug.clone()).flatten(),
+            created: message.create_time.map(|t| t as u64),
+            ..Message::new(role, text)
+        });
+    }
+
+    // Very old exports have no id, the creation time is unique enough.
+    let id = match &conversation.id {
+        Some(id) => short_id(id),
+        None => format!("{}", conversation.create_time.unwrap_or_default() as u64),
+    };
+    build_session(
+        format!("chatgpt-{}", id),
+        conversation.title.clone(),
+        conversation.create_time.map(|t| t as u64),
+        conversation.update_time.map(|t| t as u64),
+        pick_model(model_slug.as_deref(), config, CHATGPT_FALLBACK_MODEL),
+        input,
+    )
+}
+
+fn imported_source(role: &str) -> MessageSource {
+    if role == "assistant" {
+        MessageSource::Reply
+    } else {
+        MessageSource::Typed
+    }
+}
+
+fn from_claude(conversation: &ClaudeConversation, config: Option<&ConfigTC>) -> Option<Session> {
+    let mut input = Vec::new();
+    for message in &conversation.chat_messages {
+        let role = if message.sender == "assistant" {
+            "assistant"
+        } else {
+            "user"
+        };
+
+        if role == "user" {
+            for attachment in &message.attachments {
+                if !attachment.extracted_content.is_empty() {
+                    input.push(Message::from_file(
+                        &attachment.file_name,
+                        &attachment.extracted_content,
+                        MessageSource::Gf,
+                    ));
+                }
+            }
+        }
+
+        let blocks: Vec<&str> = message
+            .content
+            .iter()
+            .filter(|b| b.type_field == "text")
+            .filter_map(|b| b.text.as_deref())
+            .collect();
+        let text = if blocks.is_empty() {
+            message.text.clone()
+        } else {
+            blocks.join("\n\n")
+        };
+        if !text.trim().is_empty() {
+            input.push(Message {
+                source: imported_source(role),
+                created: message.created_at.as_deref().and_then(parse_timestamp),
+                ..Message::new(role, text)
+            });
+        }
+    }
+
+    build_session(
+        format!("claude-{}", short_id(&conversation.uuid)),
+        conversation.name.clone(),
+        conversation.created_at.as_deref().and_then(parse_timestamp),
+        conversation.updated_at.as_deref().and_then(parse_timestamp),
+        pick_model(None, config, CLAUDE_FALLBACK_MODEL),
+        input,
+    )
+}
+
+fn build_session(
+    id: String,
+    title: Option<String>,
+    created: Option<u64>,
+    updated: Option<u64>,
+    model: String,
+    input: Vec<Message>,
+) -> Option<Session> {
+    if input.iter().all(|m| m.role == "developer") {
+        return None;
+    }
+    let created = created.unwrap_or_else(now_secs);
+    let mut context = ConversationContext::new(&model, false);
+    context.input = input;
+
+    Some(Session {
+        id,
+        meta: ConversationMeta {
+            title: title.filter(|t| !t.trim().is_empty()),
+            created,
+            updated: updated.unwrap_or(created),
+            tags: Vec::new(),
+        },
+        context,
+    })
+}
+
+/// The export's model if tc knows it, otherwise the configured model.
+fn pick_model(slug: Option<&str>, config: Option<&ConfigTC>, fallback: &str) -> String {
+    match (slug, config) {
+        (Some(slug), Some(config)) if config.all_models.iter().any(|m| m == slug) => slug.into(),
+        (_, Some(config)) => config.model.clone(),
+        (Some(slug), None) => slug.into(),
+        (None, None) => fallback.into(),
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(12)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chatgpt_follows_current_branch() -> Result<(), Box<dyn Error>> {
+        let export = r#"[{
+            "id": "abc-123", "title": "Lifetimes", "create_time": 1700000000.5,
+            "update_time": 1700000100.0, "current_node": "d",
+            "mapping": {
+                "root": {"message": null, "parent": null},
+                "a": {"parent": "root", "message": {"author": {"role": "system"},
+                    "content": {"content_type": "text", "parts": [""]}}},
+                "b": {"parent": "a", "message": {"author": {"role": "user"},
+                    "content": {"content_type": "text", "parts": ["what is 'a?"]}}},
+                "old": {"parent": "b", "message": {"author": {"role": "assistant"},
+                    "content": {"content_type": "text", "parts": ["abandoned"]}}},
+                "c": {"parent": "b", "message": {"author": {"role": "tool"},
+                    "content": {"content_type": "text", "parts": ["search results"]}}},
+                "d": {"parent": "c", "message": {"author": {"role": "assistant"},
+                    "metadata": {"model_slug": "gpt-4o"},
+                    "content": {"content_type": "text", "parts": ["a lifetime"]}}}
+            }
+        }]"#;
+
+        let sessions = parse_export(export, None)?;
+        let session = sessions.first().ok_or("no session")?;
+        let contents: Vec<&str> = session
+            .context
+            .input
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+
+        assert_eq!(contents, vec!["what is 'a?", "a lifetime"]);
+        assert_eq!(session.id, "chatgpt-abc123");
+        assert_eq!(session.meta.title.as_deref(), Some("Lifetimes"));
+        assert_eq!(
+            (session.meta.created, session.meta.updated),
+            (1_700_000_000, 1_700_000_100)
+        );
+        assert_eq!(session.context.model, "gpt-4o");
+        Ok(())
+    }
+
+    #[test]
+    fn test_claude_maps_senders_and_attachments() -> Result<(), Box<dyn Error>> {
+        let export = r#"[{
+            "uuid": "f00d-beef", "name": "Review", "created_at": "2024-03-01T12:00:00.000Z",
+            "updated_at": "2024-03-01T12:05:00Z",
+            "chat_messages": [
+                {"sender": "human", "text": "look at this",
+                 "attachments": [{"file_name": "main.rs", "extracted_content": "fn main() {}"}]},
+                {"sender": "assistant", "text": "",
+                 "content": [{"type": "text", "text": "looks fine"}]}
+            ]
+        }]"#;
+
+        let sessions = parse_export(export, None)?;
+        let session = sessions.first().ok_or("no session")?;
+        let roles: Vec<&str> = session
+            .context
+            .input
+            .iter()
+            .map(|m| m.role.as_str())
+            .collect();
+
+        assert_eq!(roles, vec!["user", "user", "assistant"]);
+        assert_eq!(
+            session.context.input[0].content,
+            "main.rs\n\n:::\n\nfn main() {}"
+        );
+        assert_eq!(session.context.input[2].content, "looks fine");
+        assert_eq!(session.meta.updated - session.meta.created, 300);
+        Ok(())
+    }
+}
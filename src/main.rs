@@ -1,22 +1,43 @@
 use std::error::Error;
 
+mod branches;
+mod browser;
+mod chat;
 mod chat_client;
 mod commands;
+mod completion;
+mod context_window;
 mod conversation;
+mod critic;
+mod crypto;
+mod excerpt;
+mod export;
+mod git;
+mod history;
+mod import;
+mod mentions;
 mod message_printer;
 mod messages;
+mod model_catalog;
+mod persona;
 mod preview_md;
 mod run;
+mod search;
+mod session;
 mod spinner;
+mod storage;
 mod tc_config;
+mod templates;
 mod utils;
+mod walker;
+mod watch;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().collect();
 
     match args.len() {
-        1 => run::as_repl().await?,
+        1 => run::as_repl(None).await?,
         _ => run::as_cli_tool(&args[1..]).await?,
     }
 
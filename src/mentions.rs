@@ -0,0 +1,195 @@
+use crate::conversation::{Message, MessageSource};
+use crate::excerpt::Selection;
+use crate::utils::format_size;
+use crate::walker::{FileSet, files_under, read_text};
+use std::path::Path;
+
+// A directory listing stops here, so `@/` does not walk the whole disk.
+const MAX_LISTED: usize = 200;
+// Punctuation that ends a sentence rather than a path, as in
+// "look at @src/run.rs, please".
+const TRAILING: &[char] = &[',', '.', ';', ':', '!', '?', ')', '\'', '"'];
+
+/// A chat line with its `@path` references expanded.
+#[derive(Debug, Default)]
+pub(crate) struct Mentions {
+    /// The line as sent, with `@` dropped from references and `\@`
+    /// unescaped.
+    pub(crate) text: String,
+    /// One message per referenced file, part of a file or directory.
+    pub(crate) attachments: Vec<Message>,
+    /// References that could not be attached, with the reason.
+    pub(crate) skipped: Vec<String>,
+}
+
+/// Finds `@path`, `@path:10-80`, `@path#name` and `@dir/` at the start of
+/// words in `line`. Files are attached whole or in part, directories as a
+/// listing. An `@` inside a word (`me@example.com`), one that names nothing
+/// on disk (`@everyone`) or one written as `\@` is left as text.
+pub(crate) fn expand_mentions(line: &str) -> Mentions {
+    let mut mentions = Mentions::default();
+    let mut files = FileSet::default();
+    let mut rest = line;
+
+    while let Some(at) = rest.find('@') {
+        let (before, after) = (&rest[..at], &rest[at + 1..]);
+        if let Some(escaped) = before.strip_suffix('\\') {
+            mentions.text.push_str(escaped);
+            mentions.text.push('@');
+            rest = after;
+            continue;
+        }
+        mentions.text.push_str(before);
+
+        let at_word_start = before.is_empty() || before.ends_with(char::is_whitespace);
+        let word = after.split(char::is_whitespace).next().unwrap_or_default();
+        match resolve(word).filter(|_| at_word_start) {
+            Some(reference) => {
+                attach(reference, &mut files, &mut mentions);
+                mentions.text.push_str(reference);
+                rest = &after[reference.len()..];
+            }
+            None => {
+                mentions.text.push('@');
+                rest = after;
+            }
+        }
+    }
+    mentions.text.push_str(rest);
+
+    for (origin, content) in files.files {
+        mentions
+            .attachments
+            .push(Message::from_disk(origin, &content, MessageSource::Mention));
+    }
+    mentions.skipped.extend(
+        files
+            .skipped
+            .into_iter()
+            .map(|(path, reason)| format!("{} ({})", path, reason)),
+    );
+    mentions
+}
+
+/// The longest prefix of `word` that names something on disk, dropping
+/// trailing punctuation.
+fn resolve(word: &str) -> Option<&str> {
+    let mut candidate = word;
+    loop {
+        if !candidate.is_empty() && exists(candidate) {
+            return Some(candidate);
+        }
+        candidate = candidate.strip_suffix(TRAILING)?;
+    }
+}
+
+fn exists(reference: &str) -> bool {
+    Path::new(reference).exists()
+        || Selection::parse(reference).is_some_and(|(path, _)| Path::new(path).is_file())
+}
+
+fn attach(reference: &str, files: &mut FileSet, mentions: &mut Mentions) {
+    let path = Path::new(reference);
+    if path.is_dir() {
+        mentions.attachments.push(Message::from_file(
+            reference,
+            &directory_summary(path),
+            MessageSource::Mention,
+        ));
+    } else if path.is_file() {
+        files.add_file(path);
+    } else if let Some((path, selection)) = Selection::parse(reference) {
+        files.add_excerpt(Path::new(path), selection);
+    }
+}
+
+/// A listing of the files under `dir` with their sizes and line counts,
+/// for asking about a directory without sending all of it. Files `:gf`
+/// would skip are listed with the reason instead of a line count.
+fn directory_summary(dir: &Path) -> String {
+    let mut files = files_under(dir);
+    let mut listing = String::new();
+    let mut count = 0;
+    for file in files.by_ref().take(MAX_LISTED) {
+        count += 1;
+        let entry = match read_text(&file, true) {
+            Ok(content) => format!(
+                "{}, {} lines",
+                format_size(content.len()),
+                content.lines().count()
+            ),
+            Err(reason) => reason.to_string(),
+        };
+        listing.push_str(&format!("\n{}  ({})", file.display(), entry));
+    }
+    match files.next() {
+        Some(_) => format!("First {} files:\n{}\n…", count, listing),
+        None => format!("{} files:\n{}", count, listing),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+
+    #[test]
+    fn test_expand_mentions() -> Result<(), Box<dyn Error>> {
+        let dir = env::temp_dir().join(format!("tc_mentions_{}", std::process::id()));
+        let root = dir.display().to_string();
+        fs::create_dir_all(dir.join("src"))?;
+        fs::write(dir.join("notes.md"), "# Notes\nfirst\nsecond\n")?;
+        fs::write(dir.join("src/lib.rs"), "pub fn lib() {}\nfn private() {}\n")?;
+        fs::write(dir.join("logo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        fs::write(dir.join("Cargo.lock"), "version = 4\n")?;
+
+        let mentions = expand_mentions(&format!(
+            "explain @{root}/notes.md, and @{root}/src/lib.rs:1-1?"
+        ));
+        assert_eq!(
+            mentions.text,
+            format!("explain {root}/notes.md, and {root}/src/lib.rs:1-1?")
+        );
+        assert_eq!(mentions.attachments.len(), 2);
+        assert!(
+            mentions.attachments[1]
+                .content
+                .ends_with(":::\n\npub fn lib() {}")
+        );
+
+        let mentions = expand_mentions(r"mail me@example.com or \@src, @everyone");
+        assert_eq!(mentions.text, "mail me@example.com or @src, @everyone");
+        assert!(mentions.attachments.is_empty());
+
+        let mentions = expand_mentions(&format!("what is in @{root}/src/"));
+        assert_eq!(mentions.text, format!("what is in {root}/src/"));
+        assert!(mentions.attachments[0].content.contains("lib.rs  ("));
+
+        let listing = directory_summary(&dir.join("src"));
+        assert!(listing.starts_with("1 files:"));
+        assert!(listing.ends_with("lib.rs  (32 B, 2 lines)"));
+        let listing = directory_summary(&dir);
+        assert!(listing.contains("logo.png  (binary)"));
+        assert!(listing.contains("Cargo.lock  (lockfile)"));
+        assert!(listing.contains("notes.md  (21 B, 3 lines)"));
+
+        // Long listings stop at MAX_LISTED.
+        fs::create_dir_all(dir.join("many"))?;
+        for i in 0..MAX_LISTED + 5 {
+            fs::write(dir.join("many").join(format!("{:03}.txt", i)), "x")?;
+        }
+        let listing = directory_summary(&dir.join("many"));
+        assert!(listing.starts_with(&format!("First {} files:", MAX_LISTED)));
+        assert!(listing.ends_with('…'));
+
+        assert!(
+            !expand_mentions(&format!("@{root}/Cargo.lock"))
+                .skipped
+                .is_empty()
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{tc_config::ConfigTC, utils::calculate_message_width};
+use crate::{
+    conversation::Usage, preview_md::markdown_to_ansi, tc_config::ConfigTC,
+    utils::calculate_message_width,
+};
 use crossterm::style::{Color, Stylize};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
@@ -105,6 +108,38 @@ pub(crate) fn print_message(message_text: &str, message_type: MessageType, confi
     );
 }
 
+pub(crate) fn print_reply(reply: &str, config: &ConfigTC) {
+    let s = if config.preview_md {
+        markdown_to_ansi(reply)
+    } else {
+        reply.to_string()
+    };
+
+    if config.message_boxes_enabled {
+        print_message(&s, MessageType::Assistant, config);
+        println!();
+    } else {
+        println!("\n🤖 {}", s);
+    }
+}
+
+pub(crate) fn print_usage(usage: &Usage, config: &ConfigTC) {
+    let mut line = format!(
+        "tokens: {} in, {} out",
+        usage.input_tokens, usage.output_tokens
+    );
+    if usage.cache_read_tokens > 0 || usage.cache_creation_tokens > 0 {
+        line.push_str(&format!(
+            ", {} cache read, {} cache write",
+            usage.cache_read_tokens, usage.cache_creation_tokens
+        ));
+    }
+    println!(
+        "{}\n",
+        line.with(parse_color(&config.theme.system_color)).dim()
+    );
+}
+
 fn word_wrap(text: &str, width: usize, wrapper: String) -> String {
     let effective_width = width - 4;
     let mut result = String::new();
@@ -128,18 +163,18 @@ fn word_wrap(text: &str, width: usize, wrapper: String) -> String {
             }
 
             // Try to backtrack to the last space if we're mid-word
-            if end_pos < graphemes.len() && !graphemes[end_pos].trim().is_empty() {
-                if let Some(last_space) = graphemes[current_pos..end_pos]
+            if end_pos < graphemes.len()
+                && !graphemes[end_pos].trim().is_empty()
+                && let Some(last_space) = graphemes[current_pos..end_pos]
                     .iter()
                     .rposition(|g| g.trim().is_empty())
-                {
-                    end_pos = current_pos + last_space + 1;
-                }
+            {
+                end_pos = current_pos + last_space + 1;
             }
 
             let line_content = graphemes[current_pos..end_pos].join("");
             let final_width: usize = line_content.graphemes(true).map(|g| g.width()).sum();
-            let final_dif = (effective_width - final_width).max(0);
+            let final_dif = effective_width.saturating_sub(final_width);
 
             result.push_str(&format!(
                 "{} {}{} {}\n",
@@ -6,6 +6,10 @@ pub static MESSAGES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::n
     m.insert("developer", "Please take your time when answering. You are helpful, intelligent, and friendly.\nYou are also very concise and accurate.\nNo words are wasted in your responses.\nWhen what is being asked is ambiguous, please ask clarifying questions before answering.\nAlways answer with very accurate and kind responses that are short, to the point and friendly.");
     m.insert("document_prompt", "Please take your time when answering. Your job is to look at the following conversation\nand create a well-formed document about the topics in the conversation. Do not talk about the\npeople in the conversations, or that it is a conversation. Extract the meaning and data of\nthe conversation and put it into a well-formed report, do not omit any part of the\nconversation. If there is code, please put it in the report.\nMake sure the report is written in markdown. Make sure to look at all messages.");
     m.insert("title_prompt", "You are an assistant that creates concise titles for reports. Based on the following report content, provide a one-line title that summarizes the content. Do not include any additional text.");
+    m.insert("critic_prompt", "You are a careful reviewer. You will be given a conversation and a draft answer to its last message.\nCheck the draft for factual errors, bugs in any code, missing pieces and anything that does not answer what was asked.\nReply in exactly two sections. Start with a heading `## Critique` followed by a short list of concrete problems, or \"No issues found.\"\nThen a heading `## Revised Answer` followed by the complete improved answer, written as a direct reply to the user.\nDo not mention the review in the revised answer.");
+    m.insert("summary_prompt", "You compress chat history. Summarize the following conversation excerpt so it can replace the original messages as context.\nKeep every fact, decision, requirement, name, number and code identifier that later turns might rely on. Keep code that was agreed on.\nDrop pleasantries and repetition. Write in concise bullet points without an introduction.");
+    m.insert("commit_prompt", "You write git commit messages in the Conventional Commits format. You will be given a staged diff.\nReply with the commit message only, no code fences or commentary.\nThe first line is `type(scope): summary`, where type is one of feat, fix, docs, style, refactor, perf, test, build, ci or chore, the scope is optional and the summary is imperative and under 72 characters.\nIf the change needs explaining, add a blank line and a short body wrapped at 72 characters saying what changed and why.\nAdd a `BREAKING CHANGE:` footer only when the diff breaks a public interface.");
+    m.insert("review_prompt", "You are a senior engineer doing a code review. You will be given a diff.\nReply in markdown with exactly these sections:\n## Summary\nWhat the change does, in two or three sentences.\n## Issues\nA list of concrete problems, each starting with its severity (blocker, major or minor) and the file and line it concerns, followed by why it matters and how to fix it. Write \"None found.\" if there are none.\n## Suggestions\nOptional improvements that are not problems.\n## Tests\nWhat is tested, and what should be.\nOnly comment on what the diff shows. Do not restate the diff.");
     m.insert("readme", "Please take your time when answering. Generate a comprehensive README.md for this project. The README should include the following elements:
 
 1. **Project Title and Description**: Provide a concise overview of the project, its objectives, and key features.
@@ -0,0 +1,24 @@
+// Context windows in tokens, matched by model name prefix. The longest
+// matching prefix wins, so specific entries can override a family.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-5", 400_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("gemini", 1_048_576),
+    ("gemini-1.5-pro", 2_097_152),
+];
+
+const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+
+pub(crate) fn context_window(model: &str) -> usize {
+    CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, tokens)| *tokens)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
@@ -0,0 +1,191 @@
+use crate::conversation::{ConversationContext, Message, RequestParams};
+use crate::tc_config::{ConfigTC, get_config_path};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A named developer message with the model and sampling settings that go
+/// with it. Stored as `personas/<name>.json` next to the config file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct Persona {
+    #[serde(skip)]
+    pub(crate) name: String,
+    pub(crate) dev_message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_tokens: Option<u32>,
+}
+
+pub(crate) const INVALID_NAME: &str = "Persona names cannot contain path separators.";
+
+/// Whether `name` stays inside the personas directory.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.contains(['/', '\\']) && !name.starts_with('.')
+}
+
+impl Persona {
+    pub(crate) fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        if !is_valid_name(name) {
+            return Err(INVALID_NAME.into());
+        }
+        let path = get_personas_dir().join(format!("{}.json", name));
+        let as_str = fs::read_to_string(&path).map_err(|_| {
+            format!(
+                "No persona named '{}'. Personas live in {}",
+                name,
+                get_personas_dir().display()
+            )
+        })?;
+        let mut persona: Self = serde_json::from_str(&as_str)
+            .map_err(|e| format!("Could not parse {}: {}", path.display(), e))?;
+        persona.name = name.to_string();
+        Ok(persona)
+    }
+
+    /// Every readable persona, sorted by name.
+    pub(crate) fn list() -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(get_personas_dir()) else {
+            return vec![];
+        };
+        let mut personas: Vec<Self> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .filter_map(|p| Self::load(&p.file_stem()?.to_string_lossy()).ok())
+            .collect();
+        personas.sort_by(|a, b| a.name.cmp(&b.name));
+        personas
+    }
+
+    pub(crate) fn save(&self) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = get_personas_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", self.name));
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Swaps the developer message in `ctx` and `dev_message` for this
+    /// persona's and applies its model and settings. Returns a warning if
+    /// the preferred model is not available.
+    pub(crate) fn apply(
+        &self,
+        ctx: &mut ConversationContext,
+        dev_message: &mut Message,
+        config: &ConfigTC,
+    ) -> Option<String> {
+        dev_message.content = self.dev_message.clone();
+        match ctx.input.iter_mut().find(|m| m.role == "developer") {
+            Some(existing) => existing.content = self.dev_message.clone(),
+            None => ctx.input.insert(0, dev_message.clone()),
+        }
+        ctx.params = RequestParams {
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+        };
+
+        match &self.model {
+            Some(model) if config.all_models.contains(model) => {
+                ctx.model = model.clone();
+                None
+            }
+            Some(model) => Some(format!(
+                "Model {} is not available, keeping {}",
+                model, ctx.model
+            )),
+            None => None,
+        }
+    }
+
+    pub(crate) fn summary_line(&self) -> String {
+        let mut settings = vec![self.model.clone().unwrap_or_else(|| "any model".into())];
+        if let Some(t) = self.temperature {
+            settings.push(format!("temperature {}", t));
+        }
+        if let Some(m) = self.max_tokens {
+            settings.push(format!("max {} tokens", m));
+        }
+        format!(
+            "{:<16} {}  ({})",
+            self.name,
+            Message::new("developer", self.dev_message.as_str()).preview(40),
+            settings.join(", ")
+        )
+    }
+}
+
+pub(crate) fn get_personas_dir() -> PathBuf {
+    get_config_path().with_file_name("personas")
+}
+
+fn get_projects_path() -> PathBuf {
+    get_config_path().with_file_name("projects.json")
+}
+
+/// Project directories and the persona assigned to each.
+fn read_projects() -> HashMap<PathBuf, String> {
+    fs::read_to_string(get_projects_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Assigns `persona` to `dir` and everything below it, or removes the
+/// assignment when `persona` is `None`.
+pub(crate) fn assign_persona(dir: &Path, persona: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let mut projects = read_projects();
+    let dir = fs::canonicalize(dir)?;
+    match persona {
+        Some(name) => projects.insert(dir, name.to_string()),
+        None => projects.remove(&dir),
+    };
+    let path = get_projects_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&projects)?)?;
+    Ok(())
+}
+
+/// The persona for `dir`, from the closest assigned directory above it.
+pub(crate) fn project_persona(dir: &Path) -> Option<String> {
+    let dir = fs::canonicalize(dir).ok()?;
+    closest_assignment(&read_projects(), &dir)
+}
+
+fn closest_assignment(projects: &HashMap<PathBuf, String>, dir: &Path) -> Option<String> {
+    dir.ancestors()
+        .find_map(|ancestor| projects.get(ancestor))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_assignment_prefers_deepest_dir() {
+        let mut projects = HashMap::new();
+        projects.insert(PathBuf::from("/work"), "reviewer".to_string());
+        projects.insert(PathBuf::from("/work/site"), "web".to_string());
+
+        let find = |dir: &str| closest_assignment(&projects, Path::new(dir));
+        assert_eq!(find("/work/site/src").as_deref(), Some("web"));
+        assert_eq!(find("/work/api").as_deref(), Some("reviewer"));
+        assert_eq!(find("/home"), None);
+    }
+
+    #[test]
+    fn test_load_rejects_paths() {
+        assert!(is_valid_name("reviewer"));
+        for name in ["../../config", "sub/x", "..\\x", ".hidden"] {
+            assert!(!is_valid_name(name));
+            assert!(Persona::load(name).is_err_and(|e| e.to_string() == INVALID_NAME));
+        }
+    }
+}
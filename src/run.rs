@@ -1,39 +1,92 @@
-use crate::chat_client::{anthropic_chat, send_request, stream};
+use crate::chat::send_with_attachments;
 use crate::commands::commands_registry::TC_COMMANDS;
 use crate::commands::handle_commands::handle_command;
-use crate::conversation::{AnthropicMessage, ConversationContext, Message, ResponseC};
+use crate::completion::TcCompleter;
+use crate::conversation::{ConversationContext, Message};
+use crate::crypto;
+use crate::export::{ExportFormat, export_dir};
+use crate::history::{get_history_path, load_history, save_history};
+use crate::import::import;
+use crate::mentions::expand_mentions;
 use crate::message_printer::{MessageType, print_message};
-use crate::preview_md::markdown_to_ansi;
+use crate::persona::{Persona, project_persona};
+use crate::search::{format_hit, get_index_path, search};
+use crate::session::{Session, get_sessions_dir};
+use crate::storage::read_context;
 use crate::tc_config::{self, get_config};
-use crate::utils::calculate_message_width;
-use linefeed::{DefaultTerminal, Interface, ReadResult, complete::PathCompleter};
+use crate::utils::{calculate_message_width, read_user_input};
+use linefeed::{DefaultTerminal, Interface, ReadResult};
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-pub(crate) async fn as_repl() -> Result<(), Box<dyn Error>> {
+pub(crate) async fn as_repl(resume: Option<Session>) -> Result<(), Box<dyn Error>> {
     let config = tc_config::load_config().await?;
     print_message("~~~  Terminal Chat  ~~~", MessageType::System, &config);
 
-    if !config.openai_enabled && !config.anthropic_enabled {
+    if !config.openai_enabled
+        && !config.anthropic_enabled
+        && !config.gemini_enabled
+        && !config.azure_enabled()
+    {
         return Ok(());
     }
 
-    let context = Arc::new(Mutex::new(ConversationContext::new(
-        &config.model,
-        config.enable_streaming,
-    )));
+    let mut session = match resume {
+        Some(session) => {
+            print_message(
+                &format!(
+                    "Resumed session {} ({}, {} messages)",
+                    session.id,
+                    session.context.model,
+                    session.context.input.len()
+                ),
+                MessageType::System,
+                &config,
+            );
+            session
+        }
+        None => {
+            let mut context = ConversationContext::new(&config.model, config.enable_streaming);
+            let mut dev_message = Message::new("developer", config.dev_message.clone());
+            context.input.push(dev_message.clone());
+            if let Some(name) = std::env::current_dir()
+                .ok()
+                .and_then(|d| project_persona(&d))
+            {
+                match Persona::load(&name) {
+                    Ok(persona) => {
+                        if let Some(warning) =
+                            persona.apply(&mut context, &mut dev_message, &config)
+                        {
+                            eprintln!("{}", warning);
+                        }
+                        print_message(
+                            &format!("Using persona {} for this project", name),
+                            MessageType::System,
+                            &config,
+                        );
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            Session::new(&context)
+        }
+    };
 
-    let dev_message = Arc::new(Message {
-        role: "developer".into(),
-        content: config.dev_message.clone(),
-    });
+    let dev_message = Arc::new(Mutex::new(
+        session
+            .context
+            .input
+            .iter()
+            .find(|m| m.role == "developer")
+            .cloned()
+            .unwrap_or_else(|| Message::new("developer", config.dev_message.clone())),
+    ));
+    let context = Arc::new(Mutex::new(session.context.clone()));
     let interface = build_interface()?;
-
-    {
-        let mut locked = context.lock().await;
-        locked.input.push((*dev_message).clone());
-    }
+    let keep_history = load_history(&interface);
 
     while let ReadResult::Input(line) = interface.read_line()? {
         if line.trim().is_empty() {
@@ -41,7 +94,7 @@ pub(crate) async fn as_repl() -> Result<(), Box<dyn Error>> {
         }
         interface.add_history(line.clone());
 
-        if let Some(cmd) = line.strip_prefix(':') {
+        let result = if let Some(cmd) = line.strip_prefix(':') {
             match cmd {
                 "q" | "quit" => break,
                 _ => {
@@ -50,11 +103,20 @@ pub(crate) async fn as_repl() -> Result<(), Box<dyn Error>> {
                     {
                         eprintln!("Error executing command: {} With error: {}", cmd, e);
                     }
+                    Ok(())
                 }
             }
         } else {
-            actually_chat(line, Arc::clone(&context)).await?;
+            actually_chat(line, Arc::clone(&context)).await
+        };
+
+        if let Err(e) = session.save(&*context.lock().await) {
+            eprintln!("Could not save session {}: {}", session.id, e);
+        }
+        if keep_history && let Err(e) = save_history(&interface) {
+            eprintln!("Could not save history: {}", e);
         }
+        result?;
     }
 
     Ok(())
@@ -62,7 +124,7 @@ pub(crate) async fn as_repl() -> Result<(), Box<dyn Error>> {
 
 fn build_interface() -> Result<Interface<DefaultTerminal>, Box<dyn Error>> {
     let interface = Interface::new("terminal chat interface")?;
-    interface.set_completer(Arc::new(PathCompleter));
+    interface.set_completer(Arc::new(TcCompleter));
     interface.set_prompt("🗣️ ")?;
     Ok(interface)
 }
@@ -79,7 +141,7 @@ async fn actually_chat(
         let width = width.min(terminal_width);
 
         let line_len = line.chars().count();
-        let line_count = (line_len / width) + if line_len % width == 0 { 0 } else { 1 };
+        let line_count = (line_len / width) + if line_len.is_multiple_of(width) { 0 } else { 1 };
 
         // Clear previous lines
         for _ in 0..line_count {
@@ -88,83 +150,131 @@ async fn actually_chat(
         print_message(&line, MessageType::User, &config);
     }
 
-    ctx.input.push(Message {
-        role: "user".into(),
-        content: line.clone(),
-    });
-
-    if ctx.model.contains("claude") {
-        ctx.set_stream(false);
-
-        let reply: AnthropicMessage = anthropic_chat(&ctx).await?;
+    let mentions = expand_mentions(&line);
+    for skipped in &mentions.skipped {
+        eprintln!("Not attached: {}", skipped);
+    }
+    if !mentions.attachments.is_empty() {
+        let names: Vec<String> = mentions.attachments.iter().map(|m| m.preview(60)).collect();
+        print_message(
+            &format!("Attached: {}", names.join(", ")),
+            MessageType::System,
+            &config,
+        );
+    }
 
-        let message = reply.content.first().ok_or("No content")?.text.clone();
+    send_with_attachments(&mut ctx, mentions.attachments, mentions.text, &config).await
+}
 
-        if config.message_boxes_enabled {
-            print_message(&message, MessageType::Assistant, &config);
-            println!();
-        } else {
-            println!("🤖 {}\n", message);
-        }
-        ctx.input.push(Message {
-            role: "assistant".into(),
-            content: message.clone(),
-        });
-
-        ctx.set_stream(true);
-    } else if !config.enable_streaming || ctx.model.eq_ignore_ascii_case("gpt-4o-search-preview") {
-        ctx.set_stream(false);
-        let response: ResponseC = send_request("chat", &*ctx).await?;
-        if let Some(choice) = response.choices.first() {
-            let reply = choice.message.content.clone();
-            ctx.input.push(Message {
-                role: "assistant".into(),
-                content: reply.clone(),
-            });
-
-            let s = if config.preview_md {
-                markdown_to_ansi(&reply)
+pub(crate) async fn as_cli_tool(args: &[String]) -> Result<(), Box<dyn Error>> {
+    // One-shot commands skip the config checks but still follow saved
+    // settings such as `encrypt_storage`.
+    if let Some(Ok(config)) = tc_config::read_saved_config() {
+        *tc_config::GLOBAL_CONFIG.write()? = config;
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["-h" | "--help"] => {
+            if let Some(help_command) = TC_COMMANDS.get("help") {
+                return (help_command.run)(None).await;
             } else {
-                reply
-            };
-
-            if config.message_boxes_enabled {
-                print_message(&s, MessageType::Assistant, &config);
-                println!();
+                eprintln!("Help command not found!");
+            }
+        }
+        ["-c" | "--continue"] => match Session::latest()? {
+            Some(session) => as_repl(Some(session)).await?,
+            None => eprintln!(
+                "No saved sessions found in {}",
+                get_sessions_dir().display()
+            ),
+        },
+        ["--resume", id] => as_repl(Some(Session::load(id)?)).await?,
+        ["--resume"] => {
+            let sessions = Session::list()?;
+            if sessions.is_empty() {
+                eprintln!(
+                    "No saved sessions found in {}",
+                    get_sessions_dir().display()
+                );
             } else {
-                println!("\n🤖 {}", s);
+                println!("Saved sessions (resume with tc --resume <id>):\n");
+                for session in sessions {
+                    println!("{}", session.summary_line());
+                }
             }
         }
-        ctx.set_stream(true);
-    } else {
-        stream(&mut ctx).await?;
-    }
-
-    Ok(())
-}
+        ["search", query @ ..] if !query.is_empty() => {
+            let hits = search(&query.join(" "), 10)?;
+            if hits.is_empty() {
+                println!("No matches found.");
+                return Ok(());
+            }
+            for (i, hit) in hits.iter().enumerate() {
+                println!("{}\n", format_hit(i + 1, hit));
+            }
 
-pub(crate) async fn as_cli_tool(args: &[String]) -> Result<(), Box<dyn Error>> {
-    match args.len() {
-        1 => match args[0].as_str() {
-            "-h" | "--help" => {
-                if let Some(help_command) = TC_COMMANDS.get("help") {
-                    return (help_command.run)(None).await;
-                } else {
-                    eprintln!("Help command not found!");
-                }
+            let choice = read_user_input("Open which hit? (number, Enter to quit): ")?;
+            if let Some(hit) = choice
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| hits.get(i))
+            {
+                let context = read_context(&hit.path)?;
+                as_repl(Some(Session::new(&context))).await?;
             }
-            _ => {
-                // Handle other commands or show some usage message
+        }
+        ["export", format, dirs @ ..] if dirs.len() <= 2 => {
+            let Some(format) = ExportFormat::parse(format) else {
+                eprintln!("Unknown format: {}. Use md, html or jsonl.", format);
+                return Ok(());
+            };
+            let source = Path::new(dirs.first().copied().unwrap_or("conversations"));
+            let out = Path::new(dirs.get(1).copied().unwrap_or("exports"));
+            let theme = tc_config::read_saved_config()
+                .and_then(Result::ok)
+                .map(|c| c.theme)
+                .unwrap_or_else(tc_config::default_theme);
+            for path in export_dir(source, out, format, &theme)? {
+                println!("Wrote {}", path);
             }
-        },
+        }
+        ["import", path] => {
+            let sessions = import(Path::new(path))?;
+            println!(
+                "Imported {} conversations into {}\n",
+                sessions.len(),
+                get_sessions_dir().display()
+            );
+            for session in &sessions {
+                println!("{}", session.summary_line());
+            }
+        }
+        ["encrypt"] => {
+            crypto::passphrase(true)?;
+            crypto::check_passphrase()?;
+            let mut config = match tc_config::read_saved_config() {
+                Some(config) => config?,
+                None => tc_config::ConfigTC::default(vec![]),
+            };
+            config.encrypt_storage = true;
+            tc_config::write_config(&config, false)?;
+            *tc_config::GLOBAL_CONFIG.write()? = config;
+
+            let (encrypted, skipped) = crypto::encrypt_existing(
+                &[PathBuf::from("conversations"), get_sessions_dir()],
+                &[get_index_path(), get_history_path()],
+            )?;
+            println!(
+                "Encrypted {} files ({} already were). New files will be encrypted too.",
+                encrypted, skipped
+            );
+        }
         _ => {
             // make readme a cli command that takes the same args as in the repl
             // dir and extension
             //
             // summarize? takes any single file?
-            //
-            //
-            // Handle cases where args.len() is not 1
         }
     }
 
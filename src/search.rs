@@ -0,0 +1,371 @@
+use crate::conversation::ConversationContext;
+use crate::crypto::{read_protected, write_protected};
+use crate::session::get_sessions_dir;
+use crate::storage::{ConversationFile, modified_secs, read_context};
+use dirs::data_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SNIPPET_RADIUS: usize = 60;
+
+/// Inverted index over saved conversations and sessions, kept in the data
+/// dir and brought up to date with the files on disk before every search.
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct SearchIndex {
+    next_id: u32,
+    docs: HashMap<u32, IndexedDoc>,
+    postings: BTreeMap<String, Vec<Posting>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct IndexedDoc {
+    path: PathBuf,
+    modified: u64,
+    updated: u64,
+    model: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct Posting {
+    doc: u32,
+    message: u32,
+    count: u32,
+}
+
+pub(crate) struct SearchHit {
+    pub(crate) path: PathBuf,
+    pub(crate) updated: u64,
+    pub(crate) model: String,
+    pub(crate) snippet: String,
+    pub(crate) terms: Vec<String>,
+}
+
+/// Searches every saved conversation for `query`, best matches first.
+pub(crate) fn search(query: &str, limit: usize) -> Result<Vec<SearchHit>, Box<dyn Error>> {
+    let mut index = SearchIndex::load();
+    if index.refresh() {
+        index.save()?;
+    }
+
+    let mut hits = Vec::new();
+    for (doc_id, message, terms) in index.query(query).into_iter().take(limit) {
+        let Some(doc) = index.docs.get(&doc_id) else {
+            continue;
+        };
+        let Ok(context) = read_context(&doc.path) else {
+            continue;
+        };
+        let text = context
+            .input
+            .get(message as usize)
+            .map(|m| m.content.as_str())
+            .unwrap_or("");
+        hits.push(SearchHit {
+            path: doc.path.clone(),
+            updated: doc.updated,
+            model: doc.model.clone(),
+            snippet: snippet(text, &terms),
+            terms,
+        });
+    }
+    Ok(hits)
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| t.chars().count() > 1)
+        .map(|t| t.to_lowercase())
+}
+
+impl SearchIndex {
+    fn load() -> Self {
+        read_protected(&get_index_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = get_index_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_protected(&path, &serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Drops deleted files, reindexes changed ones and picks up new ones
+    /// from `conversations/` and the sessions dir. Returns true if anything changed.
+    fn refresh(&mut self) -> bool {
+        let mut on_disk: HashMap<PathBuf, u64> = HashMap::new();
+        for dir in [PathBuf::from("conversations"), get_sessions_dir()] {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                if path.extension().is_some_and(|e| e == "json")
+                    && let Ok(path) = fs::canonicalize(path)
+                {
+                    on_disk.insert(path.clone(), modified_secs(&path));
+                }
+            }
+        }
+
+        let mut changed = false;
+        let known: Vec<(u32, PathBuf, u64)> = self
+            .docs
+            .iter()
+            .map(|(id, doc)| (*id, doc.path.clone(), doc.modified))
+            .collect();
+        for (id, path, modified) in known {
+            // Files indexed from another working directory are kept while they exist.
+            let current = on_disk
+                .remove(&path)
+                .unwrap_or_else(|| modified_secs(&path));
+            if current != modified {
+                self.remove(id);
+                changed = true;
+                if current != 0 {
+                    on_disk.insert(path, current);
+                }
+            }
+        }
+
+        for (path, modified) in on_disk {
+            if let Ok(context) = read_context(&path) {
+                self.add(path, modified, &context);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn add(&mut self, path: PathBuf, modified: u64, context: &ConversationContext) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        for (i, msg) in context.input.iter().enumerate() {
+            if msg.role == "developer" {
+                continue;
+            }
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for term in tokenize(&msg.content) {
+                *counts.entry(term).or_default() += 1;
+            }
+            for (term, count) in counts {
+                self.postings.entry(term).or_default().push(Posting {
+                    doc: id,
+                    message: i as u32,
+                    count,
+                });
+            }
+        }
+
+        let updated = read_updated(&path).unwrap_or(modified);
+        self.docs.insert(
+            id,
+            IndexedDoc {
+                path,
+                modified,
+                updated,
+                model: context.model.clone(),
+            },
+        );
+    }
+
+    fn remove(&mut self, id: u32) {
+        self.docs.remove(&id);
+        self.postings.retain(|_, postings| {
+            postings.retain(|p| p.doc != id);
+            !postings.is_empty()
+        });
+    }
+
+    /// Ranks documents containing every query term (or a word starting with
+    /// it) by tf-idf. Returns the document, its best matching message and the
+    /// index terms that matched.
+    fn query(&self, query: &str) -> Vec<(u32, u32, Vec<String>)> {
+        let doc_count = self.docs.len().max(1) as f64;
+        let query_terms: Vec<String> = tokenize(query).collect();
+        if query_terms.is_empty() {
+            return vec![];
+        }
+
+        let mut doc_scores: HashMap<u32, f64> = HashMap::new();
+        let mut message_scores: HashMap<(u32, u32), f64> = HashMap::new();
+        let mut docs_per_term: Vec<HashSet<u32>> = Vec::new();
+        let mut matched: HashMap<u32, Vec<String>> = HashMap::new();
+
+        for query_term in &query_terms {
+            let mut docs_with_term = HashSet::new();
+            for (term, postings) in self
+                .postings
+                .range(query_term.clone()..)
+                .take_while(|(t, _)| t.starts_with(query_term.as_str()))
+            {
+                let df = postings.iter().map(|p| p.doc).collect::<HashSet<_>>().len() as f64;
+                let idf = (doc_count / df).ln() + 1.0;
+                for p in postings {
+                    let score = (1.0 + (p.count as f64).ln()) * idf;
+                    *doc_scores.entry(p.doc).or_default() += score;
+                    *message_scores.entry((p.doc, p.message)).or_default() += score;
+                    docs_with_term.insert(p.doc);
+                    let terms = matched.entry(p.doc).or_default();
+                    if !terms.contains(term) {
+                        terms.push(term.clone());
+                    }
+                }
+            }
+            docs_per_term.push(docs_with_term);
+        }
+
+        let mut ranked: Vec<(u32, f64)> = doc_scores
+            .into_iter()
+            .filter(|(doc, _)| docs_per_term.iter().all(|docs| docs.contains(doc)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ranked
+            .into_iter()
+            .map(|(doc, _)| {
+                let best_message = message_scores
+                    .iter()
+                    .filter(|((d, _), _)| *d == doc)
+                    .max_by(|a, b| a.1.total_cmp(b.1).then(b.0.1.cmp(&a.0.1)))
+                    .map(|((_, m), _)| *m)
+                    .unwrap_or(0);
+                (doc, best_message, matched.remove(&doc).unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
+/// A short window of `text` around the first matched term, on one line.
+fn snippet(text: &str, terms: &[String]) -> String {
+    let flat: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lower = flat.to_lowercase();
+    let at = terms
+        .iter()
+        .filter_map(|t| lower.find(t.as_str()))
+        .min()
+        .unwrap_or(0);
+
+    let chars: Vec<(usize, char)> = flat.char_indices().collect();
+    let center = chars.iter().position(|(i, _)| *i >= at).unwrap_or(0);
+    let start = center.saturating_sub(SNIPPET_RADIUS);
+    let end = (center + SNIPPET_RADIUS).min(chars.len());
+
+    let mut out: String = chars[start..end].iter().map(|(_, c)| c).collect();
+    if start > 0 {
+        out.insert(0, '…');
+    }
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+fn read_updated(path: &Path) -> Option<u64> {
+    ConversationFile::read(path).ok().map(|f| f.meta.updated)
+}
+
+pub(crate) fn get_index_path() -> PathBuf {
+    match data_dir() {
+        Some(dir) => dir.join("tc").join("search_index.json"),
+        None => PathBuf::from("search_index.json"),
+    }
+}
+
+/// Used by `:search` and `tc search` to show results the same way.
+pub(crate) fn format_hit(n: usize, hit: &SearchHit) -> String {
+    let mut snippet = hit.snippet.clone();
+    for term in &hit.terms {
+        if let Some(pos) = snippet.to_lowercase().find(term.as_str()) {
+            let end = pos + term.len();
+            if snippet.is_char_boundary(pos) && snippet.is_char_boundary(end) {
+                snippet = format!(
+                    "{}\x1b[1m{}\x1b[22m{}",
+                    &snippet[..pos],
+                    &snippet[pos..end],
+                    &snippet[end..]
+                );
+            }
+        }
+    }
+    let name = hit
+        .path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!(
+        "{:>2}) {}  {}  {}\n    {}",
+        n,
+        name,
+        crate::utils::format_timestamp(hit.updated),
+        hit.model,
+        snippet
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::Message;
+
+    fn context(model: &str, messages: &[&str]) -> ConversationContext {
+        let mut ctx = ConversationContext::new(model, false);
+        ctx.input
+            .push(Message::new("developer", "lifetimes lifetimes"));
+        for m in messages {
+            ctx.input.push(Message::new("user", *m));
+        }
+        ctx
+    }
+
+    #[test]
+    fn test_query_ranks_and_requires_every_term() {
+        let mut index = SearchIndex::default();
+        index.add(
+            "a.json".into(),
+            1,
+            &context(
+                "gpt-4o",
+                &[
+                    "what are lifetimes",
+                    "borrow checker and lifetimes explained",
+                ],
+            ),
+        );
+        index.add(
+            "b.json".into(),
+            1,
+            &context("claude", &["a lifetime of rust"]),
+        );
+        index.add("c.json".into(), 1, &context("o1", &["async runtimes"]));
+
+        let hits = index.query("lifetime");
+        let docs: Vec<u32> = hits.iter().map(|h| h.0).collect();
+        assert_eq!(docs, vec![0, 1]);
+
+        // Both terms must appear, and the best message is the one with both.
+        let hits = index.query("lifetimes borrow");
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].0, hits[0].1), (0, 2));
+
+        index.remove(0);
+        assert!(index.query("borrow").is_empty());
+        assert_eq!(index.query("lifetime").len(), 1);
+    }
+
+    #[test]
+    fn test_snippet_centers_on_match() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let s = snippet(&text, &["needle".to_string()]);
+        assert!(s.starts_with('…') && s.ends_with('…'));
+        assert!(s.contains("needle"));
+        assert_eq!(snippet("short text", &[]), "short text");
+    }
+}
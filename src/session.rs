@@ -0,0 +1,125 @@
+use crate::conversation::ConversationContext;
+use crate::storage::{ConversationFile, ConversationMeta, is_plain_name};
+use crate::utils::{format_timestamp, now_secs};
+use dirs::data_dir;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A REPL session, written to the data dir after every turn so it can be
+/// picked up again with `--continue` or `--resume <id>`.
+/// Stored as a `ConversationFile` named after the id.
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub(crate) id: String,
+    pub(crate) meta: ConversationMeta,
+    pub(crate) context: ConversationContext,
+}
+
+impl Session {
+    pub(crate) fn new(context: &ConversationContext) -> Self {
+        let now = now_secs();
+        Self {
+            id: format_timestamp(now)
+                .replace(['-', ':'], "")
+                .replace(' ', "-"),
+            meta: ConversationMeta {
+                created: now,
+                updated: now,
+                ..Default::default()
+            },
+            context: context.clone(),
+        }
+    }
+
+    /// Writes the session to disk. Sessions that only hold the developer
+    /// message are not worth keeping and are skipped.
+    pub(crate) fn save(&mut self, context: &ConversationContext) -> Result<(), Box<dyn Error>> {
+        if context.input.iter().all(|m| m.role == "developer") {
+            return Ok(());
+        }
+        self.meta.updated = now_secs();
+        self.context = context.clone();
+        self.write()
+    }
+
+    /// Writes the session as is, keeping its timestamps.
+    pub(crate) fn write(&self) -> Result<(), Box<dyn Error>> {
+        let path = get_sessions_dir().join(format!("{}.json", self.id));
+        ConversationFile::new(&self.context, self.meta.clone()).write(&path)
+    }
+
+    pub(crate) fn load(id: &str) -> Result<Self, Box<dyn Error>> {
+        if !is_plain_name(id) {
+            return Err(format!("Invalid session id: {}", id).into());
+        }
+        Self::read(&get_sessions_dir().join(format!("{}.json", id)))
+    }
+
+    fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = ConversationFile::read(path)?;
+        Ok(Self {
+            id: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            meta: file.meta.clone(),
+            context: file.into_context(),
+        })
+    }
+
+    /// All readable sessions, most recently updated first.
+    pub(crate) fn list() -> Result<Vec<Self>, Box<dyn Error>> {
+        let dir = get_sessions_dir();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut sessions: Vec<Self> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| Self::read(&path).ok())
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.meta.updated));
+        Ok(sessions)
+    }
+
+    pub(crate) fn latest() -> Result<Option<Self>, Box<dyn Error>> {
+        Ok(Self::list()?.into_iter().next())
+    }
+
+    pub(crate) fn summary_line(&self) -> String {
+        let first_prompt = match &self.meta.title {
+            Some(title) => title.chars().take(40).collect(),
+            None => self
+                .context
+                .input
+                .iter()
+                .find(|m| m.role == "user")
+                .map(|m| m.preview(40))
+                .unwrap_or_default(),
+        };
+        format!(
+            "{}  {}  {:<20} {:>3} msgs  {}",
+            self.id,
+            format_timestamp(self.meta.updated),
+            self.context.model,
+            self.context.input.len(),
+            first_prompt
+        )
+    }
+}
+
+pub(crate) fn get_sessions_dir() -> PathBuf {
+    if let Some(mut data_dir) = data_dir() {
+        data_dir.push("tc");
+        data_dir.push("sessions");
+        data_dir
+    } else {
+        match std::env::current_dir() {
+            Ok(dir) => dir.join("sessions"),
+            Err(_) => PathBuf::from("sessions"),
+        }
+    }
+}
@@ -16,9 +16,17 @@ where
     let result = f.await;
     spinner_running.store(false, Ordering::Relaxed);
     let _ = spinner_handle.await;
+    clear_line();
     result
 }
 
+/// Blanks the line the spinner left the cursor on, so output that follows
+/// starts clean.
+fn clear_line() {
+    print!("\r                \r");
+    stdout().flush().ok();
+}
+
 // pub async fn run_with_loader<F, T>(f: F) -> T
 // where
 //     F: std::future::Future<Output = T>,
@@ -0,0 +1,228 @@
+use crate::branches::Branch;
+use crate::conversation::{Alternate, ConversationContext, Message, RequestParams};
+use crate::crypto::{read_protected, write_protected};
+use crate::utils::now_secs;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Version 1 was `ConversationContext` serialized as is, either on its own
+/// (`:sc`) or wrapped in an auto-saved session. It had no `version` field.
+pub(crate) const FORMAT_VERSION: u32 = 2;
+
+/// Information about a conversation as a whole.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct ConversationMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) title: Option<String>,
+    #[serde(default)]
+    pub(crate) created: u64,
+    #[serde(default)]
+    pub(crate) updated: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+}
+
+/// A conversation as written to disk, for both `:sc` files and sessions.
+/// Kept apart from the provider requests so either can change on its own.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ConversationFile {
+    pub(crate) version: u32,
+    #[serde(flatten)]
+    pub(crate) meta: ConversationMeta,
+    pub(crate) model: String,
+    pub(crate) messages: Vec<Message>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) branches: Vec<Branch>,
+    #[serde(default)]
+    pub(crate) active_branch: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) alternates: Vec<Alternate>,
+    #[serde(default, skip_serializing_if = "RequestParams::is_unset")]
+    pub(crate) params: RequestParams,
+}
+
+/// The shape of an auto-saved session before versioning.
+#[derive(Deserialize)]
+struct LegacySession {
+    created: u64,
+    updated: u64,
+    #[serde(default)]
+    title: Option<String>,
+    context: ConversationContext,
+}
+
+impl ConversationMeta {
+    /// Metadata for a conversation being saved now, dated from its first
+    /// timestamped message.
+    pub(crate) fn new(context: &ConversationContext, title: Option<String>) -> Self {
+        let now = now_secs();
+        Self {
+            title,
+            created: context.input.iter().find_map(|m| m.created).unwrap_or(now),
+            updated: now,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl ConversationFile {
+    pub(crate) fn new(context: &ConversationContext, meta: ConversationMeta) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            meta,
+            model: context.model.clone(),
+            messages: context.input.clone(),
+            branches: context.branches.clone(),
+            active_branch: context.active_branch,
+            alternates: context.alternates.clone(),
+            params: context.params,
+        }
+    }
+
+    pub(crate) fn into_context(self) -> ConversationContext {
+        // Requests that must not stream turn it off for themselves, so a
+        // loaded conversation starts out the same as a new one.
+        let mut context = ConversationContext::new(&self.model, true);
+        context.input = self.messages;
+        context.branches = self.branches;
+        context.active_branch = self.active_branch;
+        context.alternates = self.alternates;
+        context.params = self.params;
+        context
+    }
+
+    /// Reads a conversation file of any version, migrating older ones.
+    pub(crate) fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let as_str = read_protected(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::migrate(serde_json::from_str(&as_str)?, modified_secs(path))
+            .map_err(|e| format!("Could not load {}: {}", path.display(), e).into())
+    }
+
+    /// Writes the file through a temporary copy, so a crash mid-write
+    /// never corrupts the last good version.
+    pub(crate) fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        write_protected(&tmp, &serde_json::to_string(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn migrate(value: Value, modified: u64) -> Result<Self, Box<dyn Error>> {
+        match value.get("version").and_then(Value::as_u64) {
+            Some(version) if version > u64::from(FORMAT_VERSION) => Err(format!(
+                "format version {} is newer than this tc supports ({})",
+                version, FORMAT_VERSION
+            )
+            .into()),
+            Some(_) => Ok(serde_json::from_value(value)?),
+            None if value.get("context").is_some() => {
+                let legacy: LegacySession = serde_json::from_value(value)?;
+                let meta = ConversationMeta {
+                    title: legacy.title,
+                    created: legacy.created,
+                    updated: legacy.updated,
+                    tags: Vec::new(),
+                };
+                Ok(Self::new(&legacy.context, meta))
+            }
+            None => {
+                let context: ConversationContext = serde_json::from_value(value)?;
+                let meta = ConversationMeta {
+                    created: modified,
+                    updated: modified,
+                    ..Default::default()
+                };
+                Ok(Self::new(&context, meta))
+            }
+        }
+    }
+}
+
+/// Reads any saved conversation, a `:sc` file or a session, ready to chat.
+pub(crate) fn read_context(path: &Path) -> Result<ConversationContext, Box<dyn Error>> {
+    Ok(ConversationFile::read(path)?.into_context())
+}
+
+/// Whether `name` can be used as a file name inside a storage directory
+/// without reaching outside it.
+pub(crate) fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && !name.contains("..")
+}
+
+pub(crate) fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrates_legacy_files() -> Result<(), Box<dyn Error>> {
+        let sc_file =
+            r#"{"model":"gpt-4o","input":[{"role":"user","content":"hi"}],"stream":false}"#;
+        let file = ConversationFile::migrate(serde_json::from_str(sc_file)?, 42)?;
+        assert_eq!(file.version, FORMAT_VERSION);
+        assert_eq!((file.meta.created, file.meta.updated), (42, 42));
+        assert_eq!(file.messages, vec![Message::new("user", "hi")]);
+
+        let session = format!(
+            r#"{{"id":"x","created":1,"updated":2,"context":{}}}"#,
+            sc_file
+        );
+        let file = ConversationFile::migrate(serde_json::from_str(&session)?, 42)?;
+        assert_eq!((file.meta.created, file.meta.updated), (1, 2));
+        assert_eq!(file.model, "gpt-4o");
+
+        let newer = r#"{"version":99,"model":"m","messages":[]}"#;
+        assert!(ConversationFile::migrate(serde_json::from_str(newer)?, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_plain_name() {
+        assert!(is_plain_name("notes on rust"));
+        assert!(!is_plain_name("../../x"));
+        assert!(!is_plain_name("sub/name"));
+        assert!(!is_plain_name("..\\x"));
+        assert!(!is_plain_name(""));
+    }
+
+    #[test]
+    fn test_round_trip_keeps_message_metadata() -> Result<(), Box<dyn Error>> {
+        let mut context = ConversationContext::new("gpt-4o", true);
+        context.input.push(Message {
+            pinned: true,
+            ..Message::new("user", "hi")
+        });
+        context.input.push(Message::reply(
+            "gpt-4o",
+            "hello",
+            std::time::Duration::from_millis(1500),
+            None,
+        ));
+
+        let file = ConversationFile::new(&context, ConversationMeta::new(&context, None));
+        let json = serde_json::to_string(&file)?;
+        assert!(!json.contains("stream"));
+        assert!(json.contains(r#""version":2"#));
+
+        let loaded = ConversationFile::migrate(serde_json::from_str(&json)?, 0)?.into_context();
+        assert_eq!(loaded.input, context.input);
+        assert_eq!(loaded.input[1].elapsed_ms, Some(1500));
+        Ok(())
+    }
+}
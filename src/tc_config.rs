@@ -1,10 +1,10 @@
 use crate::{
-    messages::MESSAGES,
+    templates::prompt,
     utils::{confirm_action, read_user_input, sequence_equals},
 };
 use dirs::config_dir;
 use serde::{Deserialize, Serialize};
-use std::{env, error::Error, fs::File, path::PathBuf, sync::LazyLock};
+use std::{collections::HashMap, env, error::Error, fs::File, path::PathBuf, sync::LazyLock};
 
 use crossterm::style::{Color, Stylize};
 use std::sync::RwLock;
@@ -32,18 +32,98 @@ pub(crate) struct ConfigTC {
     #[serde(default = "default_openai")]
     pub(crate) openai_enabled: bool,
 
+    #[serde(default = "default_gemini")]
+    pub(crate) gemini_enabled: bool,
+
     #[serde(default)]
     pub(crate) message_boxes_enabled: bool,
 
     #[serde(default = "default_theme")]
     pub(crate) theme: Theme,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) azure: Option<AzureConfig>,
+
+    #[serde(default)]
+    pub(crate) critic_model: Option<String>,
+
+    #[serde(default)]
+    pub(crate) critic_keep: CriticKeep,
+
+    #[serde(default)]
+    pub(crate) context_strategy: ContextStrategy,
+
+    /// Encrypt saved conversations, sessions, the search index and prompt
+    /// history. Turned on by `tc encrypt`, which also converts existing files.
+    #[serde(default)]
+    pub(crate) encrypt_storage: bool,
+
+    /// `:gf`, `:diff`, `:review` and `:commitmsg` ask before sending more
+    /// than this many estimated tokens of files or diff.
+    #[serde(default = "default_gf_token_budget")]
+    pub(crate) gf_token_budget: usize,
+
+    /// What to do with `:gf` files that change on disk: nothing, refresh
+    /// them before each send, or mark them as out of date. Set with `:watch`.
+    #[serde(default)]
+    pub(crate) watch_files: WatchMode,
+}
+
+/// What to do when a conversation outgrows the model's context window.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ContextStrategy {
+    Truncate,
+    #[default]
+    Summarize,
+    Refuse,
+}
+
+/// What happens before a send to files added with `:gf` that have since
+/// changed on disk.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WatchMode {
+    #[default]
+    Off,
+    Replace,
+    Annotate,
+}
+
+/// Which version of a critiqued reply stays in the conversation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CriticKeep {
+    Draft,
+    #[default]
+    Revised,
+    All,
+}
+
+/// Azure OpenAI resource. `deployments` maps the model names shown in tc
+/// to the deployment names created in the Azure portal.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct AzureConfig {
+    pub(crate) endpoint: String,
+
+    #[serde(default = "default_azure_api_version")]
+    pub(crate) api_version: String,
+
+    #[serde(default)]
+    pub(crate) deployments: HashMap<String, String>,
+}
+
+impl AzureConfig {
+    pub(crate) fn deployment(&self, model: &str) -> Option<&str> {
+        self.deployments.get(model).map(String::as_str)
+    }
 }
 
 pub(crate) static GLOBAL_CONFIG: LazyLock<RwLock<ConfigTC>> =
     LazyLock::new(|| RwLock::new(ConfigTC::default(vec![])));
 
 fn default_dev_message() -> String {
-    MESSAGES["developer"].to_string()
+    prompt("developer").unwrap_or_default()
 }
 
 fn default_anthropic() -> bool {
@@ -54,47 +134,78 @@ fn default_openai() -> bool {
     env::var("OPENAI_API_KEY").is_ok()
 }
 
+fn default_gemini() -> bool {
+    env::var("GEMINI_API_KEY").is_ok()
+}
+
+fn default_gf_token_budget() -> usize {
+    50_000
+}
+
+fn default_azure_api_version() -> String {
+    "2025-04-01-preview".to_string()
+}
+
 pub async fn load_config() -> Result<ConfigTC, Box<dyn Error>> {
     let anthropic_enabled = default_anthropic();
     let openai_enabled = default_openai();
+    let gemini_enabled = default_gemini();
 
-    if !anthropic_enabled && !openai_enabled {
+    let saved = read_saved_config();
+    let azure = saved
+        .as_ref()
+        .and_then(|c| c.as_ref().ok())
+        .filter(|c| c.azure_enabled())
+        .and_then(|c| c.azure.clone());
+
+    if !anthropic_enabled && !openai_enabled && !gemini_enabled && azure.is_none() {
         eprintln!(
-            "\nNo API keys detected. You must have an Anthropic and/or an OpenAI key to use this app.\n"
+            "\nNo API keys detected. You must have an Anthropic, OpenAI, Gemini and/or Azure OpenAI key to use this app.\n"
         );
         return Ok(ConfigTC::default(vec![]));
     }
 
-    let all_models = crate::utils::get_all_model_names(anthropic_enabled, openai_enabled).await?;
-
-    let rv = if let Ok(file) = File::open(get_config_path()) {
-        match serde_json::from_reader::<File, ConfigTC>(file) {
-            Ok(mut config) => {
-                if !sequence_equals(&config.all_models, &all_models) {
-                    config.all_models = all_models.clone();
-                    // Update the configuration file with the new models list
-                    write_config(&config, false)?;
-                }
-                if !all_models.contains(&config.model) {
-                    let first = all_models.first().ok_or("No models found")?;
-                    eprintln!("\nInvalid model found in config. Using: {}", first);
-                    config.model = first.to_owned();
-                }
-                config
+    let mut all_models =
+        crate::utils::get_all_model_names(anthropic_enabled, openai_enabled, gemini_enabled)
+            .await?;
+    if let Some(azure) = &azure {
+        let mut friendly: Vec<&String> = azure.deployments.keys().collect();
+        friendly.sort();
+        for name in friendly {
+            if !all_models.contains(name) {
+                all_models.push(name.clone());
             }
-            Err(_) => {
-                println!("\nFailed to load config. Using default values.");
-                ConfigTC::default(all_models)
+        }
+    }
+
+    let rv = match saved {
+        Some(Ok(mut config)) => {
+            if !sequence_equals(&config.all_models, &all_models) {
+                config.all_models = all_models.clone();
+                // Update the configuration file with the new models list
+                write_config(&config, false)?;
             }
+            if !all_models.contains(&config.model) {
+                let first = all_models.first().ok_or("No models found")?;
+                eprintln!("\nInvalid model found in config. Using: {}", first);
+                config.model = first.to_owned();
+            }
+            config
+        }
+        Some(Err(_)) => {
+            println!("\nFailed to load config. Using default values.");
+            ConfigTC::default(all_models)
+        }
+        None if confirm_action("No config file found. Would you like to set one up? (y/n)") => {
+            let mut config = ConfigTC::default(all_models.clone());
+            config_interview(&mut config);
+            write_config(&config, true)?;
+            config
+        }
+        None => {
+            println!("Using default values.");
+            ConfigTC::default(all_models)
         }
-    } else if confirm_action("No config file found. Would you like to set one up? (y/n)") {
-        let mut config = ConfigTC::default(all_models.clone());
-        config_interview(&mut config);
-        write_config(&config, true)?;
-        config
-    } else {
-        println!("Using default values.");
-        ConfigTC::default(all_models)
     };
 
     let mut global = GLOBAL_CONFIG.write()?;
@@ -102,6 +213,13 @@ pub async fn load_config() -> Result<ConfigTC, Box<dyn Error>> {
     Ok(rv)
 }
 
+/// Reads the config file without checking keys or models, for one-shot CLI commands.
+pub(crate) fn read_saved_config() -> Option<Result<ConfigTC, serde_json::Error>> {
+    File::open(get_config_path())
+        .ok()
+        .map(serde_json::from_reader::<File, ConfigTC>)
+}
+
 pub fn get_config() -> Result<ConfigTC, Box<dyn Error>> {
     match GLOBAL_CONFIG.read() {
         Ok(gc) => Ok(gc.clone()),
@@ -153,10 +271,33 @@ impl ConfigTC {
             preview_md: false,
             anthropic_enabled: default_anthropic(),
             openai_enabled: default_openai(),
+            gemini_enabled: default_gemini(),
             message_boxes_enabled: false,
             theme: default_theme(),
+            azure: None,
+            critic_model: None,
+            critic_keep: CriticKeep::default(),
+            context_strategy: ContextStrategy::default(),
+            encrypt_storage: false,
+            gf_token_budget: default_gf_token_budget(),
+            watch_files: WatchMode::default(),
         }
     }
+
+    pub(crate) fn azure_enabled(&self) -> bool {
+        self.azure.is_some() && env::var("AZURE_OPENAI_API_KEY").is_ok()
+    }
+
+    /// The Azure deployment serving `model`, if it is routed through Azure.
+    pub(crate) fn azure_deployment(&self, model: &str) -> Option<(&AzureConfig, &str)> {
+        if !self.azure_enabled() {
+            return None;
+        }
+        let azure = self.azure.as_ref()?;
+        azure
+            .deployment(model)
+            .map(|deployment| (azure, deployment))
+    }
 }
 
 pub fn config_interview(config: &mut ConfigTC) {
@@ -168,10 +309,11 @@ pub fn config_interview(config: &mut ConfigTC) {
     config.model = loop {
         let input =
             read_user_input("Please select a model by typing its number:").unwrap_or_default();
-        if let Ok(num) = input.trim().parse::<usize>() {
-            if num > 0 && num <= config.all_models.len() {
-                break config.all_models[num - 1].clone();
-            }
+        if let Ok(num) = input.trim().parse::<usize>()
+            && num > 0
+            && num <= config.all_models.len()
+        {
+            break config.all_models[num - 1].clone();
         }
         eprintln!("\nInvalid model selection. Please try again.");
     };
@@ -190,6 +332,17 @@ pub fn config_interview(config: &mut ConfigTC) {
         config.preview_md = false;
     }
 
+    println!("\nWhen a conversation nears the model's context window:");
+    println!("1) Summarize older turns\n2) Drop older turns\n3) Refuse to send");
+    config.context_strategy = match read_user_input("Please choose 1, 2 or 3:")
+        .unwrap_or_default()
+        .as_str()
+    {
+        "2" => ContextStrategy::Truncate,
+        "3" => ContextStrategy::Refuse,
+        _ => ContextStrategy::Summarize,
+    };
+
     if confirm_action("Write a custom developer message for the AI? (y/n)") {
         config.dev_message =
             read_user_input("Enter your custom message:").unwrap_or_else(|_| default_dev_message());
@@ -246,16 +399,34 @@ fn read_valid_color(prompt: &str) -> String {
 
 pub(crate) fn print_config(config: &ConfigTC) {
     println!(
-        "\nConfiguration:\nModel: {}\nEnable Streaming: {}\nPreview Markdown: {}\nMessage Boxes: {}\nDeveloper Message:\n {}\nTheme Colors: System: {}, User: {}, Assistant: {}",
+        "\nConfiguration:\nModel: {}\nEnable Streaming: {}\nPreview Markdown: {}\nMessage Boxes: {}\nContext Strategy: {:?}\nGf Token Budget: {}\nWatch Files: {:?}\nDeveloper Message:\n {}\nTheme Colors: System: {}, User: {}, Assistant: {}",
         config.model,
         config.enable_streaming,
         config.preview_md,
         config.message_boxes_enabled,
+        config.context_strategy,
+        config.gf_token_budget,
+        config.watch_files,
         config.dev_message,
         config.theme.system_color,
         config.theme.user_color,
         config.theme.assistant_color
     );
+    if config.encrypt_storage {
+        println!("Storage: encrypted");
+    }
+    if let Some(critic) = &config.critic_model {
+        println!("Critic: {} (keeping {:?})", critic, config.critic_keep);
+    }
+    if let Some(azure) = &config.azure {
+        println!(
+            "Azure OpenAI: {} (api-version {}, streaming off)",
+            azure.endpoint, azure.api_version
+        );
+        for (name, deployment) in &azure.deployments {
+            println!("  {} -> {}", name, deployment);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -282,7 +453,7 @@ fn default_assistant_color() -> String {
     "blue".to_string()
 }
 
-fn default_theme() -> Theme {
+pub(crate) fn default_theme() -> Theme {
     Theme {
         system_color: default_system_color(),
         user_color: default_user_color(),
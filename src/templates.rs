@@ -0,0 +1,142 @@
+use crate::messages::MESSAGES;
+use crate::tc_config::get_config_path;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Reads template `name`: the user's `templates/<name>.md` next to the
+/// config file, or else the built-in prompt of the same name. Overriding a
+/// built-in such as `document_prompt` changes what `:doc` sends.
+pub(crate) fn template(name: &str) -> Option<String> {
+    if name.contains(['/', '\\']) {
+        return None;
+    }
+    fs::read_to_string(get_templates_dir().join(format!("{}.md", name)))
+        .ok()
+        .or_else(|| MESSAGES.get(name).map(|m| m.to_string()))
+}
+
+/// A built-in prompt, as overridden by the user if they have.
+pub(crate) fn prompt(name: &str) -> Result<String, Box<dyn Error>> {
+    template(name).ok_or_else(|| format!("Missing {}", name).into())
+}
+
+/// Names of the user's templates and the built-in prompts, sorted.
+pub(crate) fn template_names() -> Vec<String> {
+    let mut names: BTreeSet<String> = MESSAGES.keys().map(|k| k.to_string()).collect();
+    if let Ok(entries) = fs::read_dir(get_templates_dir()) {
+        names.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "md"))
+                .filter_map(|p| Some(p.file_stem()?.to_string_lossy().to_string())),
+        );
+    }
+    names.into_iter().collect()
+}
+
+pub(crate) fn get_templates_dir() -> PathBuf {
+    get_config_path().with_file_name("templates")
+}
+
+pub(crate) fn needs_input(template: &str) -> bool {
+    template
+        .split("{{")
+        .skip(1)
+        .filter_map(|part| part.split_once("}}"))
+        .any(|(key, _)| key.trim() == "input")
+}
+
+/// Fills in `{{input}}`, `{{clipboard}}`, `{{file:path}}` and
+/// `{{shell:cmd}}`. Anything else in braces is left as written.
+pub(crate) fn expand(template: &str, input: &str) -> Result<String, Box<dyn Error>> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}").map(|c| open + c) else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        let key = rest[open + 2..close].trim();
+
+        if key == "input" {
+            out.push_str(input);
+        } else if key == "clipboard" {
+            out.push_str(&read_clipboard()?);
+        } else if let Some(path) = key.strip_prefix("file:") {
+            let path = path.trim();
+            let content =
+                fs::read_to_string(path).map_err(|e| format!("{{{{file:{}}}}}: {}", path, e))?;
+            out.push_str(&content);
+        } else if let Some(cmd) = key.strip_prefix("shell:") {
+            out.push_str(&run_shell(cmd.trim())?);
+        } else {
+            out.push_str(&rest[open..close + 2]);
+        }
+        rest = &rest[close + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn run_shell(cmd: &str) -> Result<String, Box<dyn Error>> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", cmd]).output()?
+    } else {
+        Command::new("sh").args(["-c", cmd]).output()?
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "{{{{shell:{}}}}} failed: {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Asks whichever clipboard tool the platform has.
+fn read_clipboard() -> Result<String, Box<dyn Error>> {
+    let tools: &[(&str, &[&str])] = &[
+        ("pbpaste", &[]),
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+        ("powershell", &["-NoProfile", "-Command", "Get-Clipboard"]),
+    ];
+    for (program, args) in tools {
+        if let Ok(output) = Command::new(program).args(*args).output()
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+    }
+    Err("Could not read the clipboard. Install wl-paste, xclip or xsel.".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_placeholders() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            expand("Review {{ input }} please", "this diff")?,
+            "Review this diff please"
+        );
+        // Unknown placeholders and unclosed braces are kept.
+        assert_eq!(expand("{{name}} and {{input", "x")?, "{{name}} and {{input");
+
+        let manifest = expand("{{file:Cargo.toml}}", "")?;
+        assert!(manifest.contains("[package]"));
+        assert!(expand("{{file:does/not/exist}}", "").is_err());
+        Ok(())
+    }
+}
@@ -3,56 +3,16 @@ use linefeed::{Interface, ReadResult};
 use crate::{
     chat_client::get_models, commands::change_model::ModelsResponse, conversation::Response,
 };
-use std::fs;
-use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{collections::HashSet, error::Error};
 
-pub(crate) fn walk_directory(
-    path: &str,
-    extensions: &HashSet<&str>,
-    excluded_dirs: &HashSet<&str>,
-) -> std::io::Result<Vec<(String, String)>> {
-    let mut results = Vec::new();
-
-    if Path::new(path).is_dir() {
-        visit_files(Path::new(path), extensions, excluded_dirs, &mut results)?;
-    }
-
-    Ok(results)
-}
-
-fn visit_files(
-    path: &Path,
-    extensions: &HashSet<&str>,
-    excluded_dirs: &HashSet<&str>,
-    results: &mut Vec<(String, String)>,
-) -> std::io::Result<()> {
-    if path.is_dir() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-                    if excluded_dirs.contains(dir_name) {
-                        continue;
-                    }
-                }
-                visit_files(&path, extensions, excluded_dirs, results)?;
-            } else if path.is_file() {
-                let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !filename.starts_with('.') {
-                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-                    if extensions.is_empty() || extensions.contains(ext) {
-                        if let Ok(content) = fs::read_to_string(&path) {
-                            results.push((path.display().to_string(), content));
-                        }
-                    }
-                }
-            }
-        }
+/// `1234` as `1.2 KB` and so on.
+pub(crate) fn format_size(bytes: usize) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
     }
-    Ok(())
 }
 
 pub fn calculate_message_width(
@@ -73,12 +33,11 @@ pub fn calculate_message_width(
 
 pub fn extract_message_text(response: &Response) -> Option<String> {
     for output in &response.output {
-        if output.type_field == "message" {
-            if let Some(content) = &output.content {
-                if let Some(first_content) = content.first() {
-                    return Some(first_content.text.clone());
-                }
-            }
+        if output.type_field == "message"
+            && let Some(content) = &output.content
+            && let Some(first_content) = content.first()
+        {
+            return Some(first_content.text.clone());
         }
     }
     None
@@ -93,6 +52,17 @@ pub fn read_user_input(prompt: &str) -> Result<String, Box<dyn Error>> {
     unreachable!()
 }
 
+/// Like `read_user_input`, with `initial` already typed in so it can be edited.
+pub fn read_user_input_with(prompt: &str, initial: &str) -> Result<String, Box<dyn Error>> {
+    let interface = Interface::new("tc")?;
+    interface.set_prompt(prompt)?;
+    interface.set_buffer(initial)?;
+    match interface.read_line()? {
+        ReadResult::Input(line) => Ok(line.trim().to_string()),
+        _ => Err("input closed".into()),
+    }
+}
+
 pub fn confirm_action(prompt: &str) -> bool {
     let response = read_user_input(prompt);
     response.is_ok_and(|c| c.eq_ignore_ascii_case("y"))
@@ -106,30 +76,101 @@ const OPENAI_MODELS: &[&str] = &[
     "o3-mini",
 ];
 
+const GEMINI_MODELS: &[&str] = &["gemini-2.5-pro", "gemini-2.5-flash", "gemini-2.0-flash"];
+
 pub async fn get_all_model_names(
     anthropic_enabled: bool,
     openai_enabled: bool,
+    gemini_enabled: bool,
 ) -> Result<Vec<String>, Box<dyn Error>> {
-    let models_response: ModelsResponse = if anthropic_enabled {
-        serde_json::from_str(&get_models().await?)?
-    } else {
-        ModelsResponse { data: vec![] }
-    };
+    let mut names: Vec<String> = Vec::new();
 
-    let names: Vec<String> = models_response.data.into_iter().map(|m| m.id).collect();
-    if openai_enabled && anthropic_enabled {
-        Ok(OPENAI_MODELS
-            .iter()
-            .map(|&model| model.to_string())
-            .chain(names)
-            .collect())
-    } else if openai_enabled {
-        Ok(OPENAI_MODELS.iter().map(|m| m.to_string()).collect())
-    } else if anthropic_enabled {
-        Ok(names)
-    } else {
-        unreachable!()
+    if openai_enabled {
+        names.extend(OPENAI_MODELS.iter().map(|m| m.to_string()));
     }
+    if anthropic_enabled {
+        let models_response: ModelsResponse = serde_json::from_str(&get_models().await?)?;
+        names.extend(models_response.data.into_iter().map(|m| m.id));
+    }
+    if gemini_enabled {
+        names.extend(GEMINI_MODELS.iter().map(|m| m.to_string()));
+    }
+
+    Ok(names)
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats unix seconds as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Parses an RFC 3339 timestamp such as `2024-03-01T12:34:56.789Z` into
+/// seconds since the epoch. Fractions are dropped, offsets are applied.
+pub(crate) fn parse_timestamp(text: &str) -> Option<u64> {
+    let (date, time) = text.trim().split_once(['T', ' '])?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(at) => time.split_at(at),
+        None => (time, ""),
+    };
+    let mut clock = clock.splitn(3, ':');
+    let hour = clock.next()?.parse::<i64>().ok()?;
+    let minute = clock.next()?.parse::<i64>().ok()?;
+    let second = clock
+        .next()
+        .map(|s| s.split('.').next().unwrap_or("0").parse::<i64>().ok())
+        .unwrap_or(Some(0))?;
+
+    let offset_secs = match offset.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let (h, m) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+            let secs = h.parse::<i64>().ok()? * 3_600 + m.parse::<i64>().ok()? * 60;
+            if sign == '+' { secs } else { -secs }
+        }
+        _ => 0,
+    };
+
+    // Days-from-civil, the inverse of the algorithm in format_timestamp.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    u64::try_from(days * 86_400 + hour * 3_600 + minute * 60 + second - offset_secs).ok()
 }
 
 pub(crate) fn sequence_equals(slice1: &[String], slice2: &[String]) -> bool {
@@ -176,4 +217,32 @@ mod tests {
         // Empty sequences
         assert!(sequence_equals(&[], &[]));
     }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_792_418_645), "2026-10-19 14:04:05");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_timestamp("2000-02-29T00:00:00.123456Z"),
+            Some(951_782_400)
+        );
+        assert_eq!(
+            parse_timestamp("2026-10-19T16:04:05+02:00"),
+            Some(1_792_418_645)
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+    }
 }
@@ -0,0 +1,386 @@
+use crate::excerpt::Selection;
+use crate::utils::format_size;
+use crate::watch::FileOrigin;
+use glob::{MatchOptions, Pattern};
+use ignore::{Walk, WalkBuilder};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files bigger than this are left out.
+pub(crate) const MAX_FILE_BYTES: usize = 512 * 1024;
+/// Once this much has been collected, the remaining files are left out.
+pub(crate) const MAX_TOTAL_BYTES: usize = 4 * 1024 * 1024;
+/// Ignore file read alongside `.gitignore` and `.ignore`, for things only
+/// tc should skip.
+pub(crate) const TC_IGNORE: &str = ".tcignore";
+
+// Build output is skipped even without a `.gitignore`, as before.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+];
+// Git uses the same test: a NUL byte near the start means binary.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Why a file was not collected.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SkipReason {
+    Binary,
+    Lockfile,
+    TooLarge(usize),
+    OverTotal,
+    Unreadable(String),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Binary => write!(f, "binary"),
+            SkipReason::Lockfile => write!(f, "lockfile"),
+            SkipReason::TooLarge(size) => write!(
+                f,
+                "{} is over the {} file limit",
+                format_size(*size),
+                format_size(MAX_FILE_BYTES)
+            ),
+            SkipReason::OverTotal => {
+                write!(f, "over the {} total limit", format_size(MAX_TOTAL_BYTES))
+            }
+            SkipReason::Unreadable(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Files collected for the context, where each came from, and the ones
+/// left out with the reason.
+#[derive(Debug, Default)]
+pub(crate) struct FileSet {
+    pub(crate) files: Vec<(FileOrigin, String)>,
+    pub(crate) skipped: Vec<(String, SkipReason)>,
+    total_bytes: usize,
+}
+
+impl FileSet {
+    /// Adds one file if it is text, not a lockfile and within the limits.
+    pub(crate) fn add_file(&mut self, path: &Path) {
+        self.add(path, None);
+    }
+
+    /// Adds part of a file. The limits apply to the part, not the file.
+    pub(crate) fn add_excerpt(&mut self, path: &Path, selection: Selection) {
+        self.add(path, Some(selection));
+    }
+
+    fn add(&mut self, path: &Path, selection: Option<Selection>) {
+        let name = path.display().to_string();
+        let content = read_text(path, selection.is_none()).and_then(|content| match &selection {
+            Some(selection) => selection
+                .extract(&name, &content)
+                .map_err(SkipReason::Unreadable),
+            None => Ok(content),
+        });
+        match content.and_then(|content| self.check_size(content)) {
+            Ok(content) => {
+                self.total_bytes += content.len();
+                self.files
+                    .push((FileOrigin::new(&name, selection, &content), content));
+            }
+            Err(reason) => {
+                let label = match selection {
+                    Some(selection) => format!("{}{}", name, selection),
+                    None => name,
+                };
+                self.skipped.push((label, reason));
+            }
+        }
+    }
+
+    fn check_size(&self, content: String) -> Result<String, SkipReason> {
+        if content.len() > MAX_FILE_BYTES {
+            return Err(SkipReason::TooLarge(content.len()));
+        }
+        if self.total_bytes + content.len() > MAX_TOTAL_BYTES {
+            return Err(SkipReason::OverTotal);
+        }
+        Ok(content)
+    }
+
+    /// Adds the files under `dir` that `keep` accepts, honoring
+    /// `.gitignore`, `.ignore` and `.tcignore` and skipping hidden files.
+    pub(crate) fn add_dir(&mut self, dir: &Path, keep: impl Fn(&Path) -> bool) {
+        for entry in walk(dir) {
+            match entry {
+                Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                    if keep(entry.path()) {
+                        self.add_file(entry.path());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => self.skipped.push((
+                    dir.display().to_string(),
+                    SkipReason::Unreadable(e.to_string()),
+                )),
+            }
+        }
+    }
+
+    /// One line per skipped file, for showing after the files were added.
+    pub(crate) fn skipped_report(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+        let mut report = format!("Skipped {} files:", self.skipped.len());
+        for (path, reason) in &self.skipped {
+            report.push_str(&format!("\n  {} ({})", path, reason));
+        }
+        Some(report)
+    }
+}
+
+fn walk(dir: &Path) -> Walk {
+    WalkBuilder::new(dir)
+        .require_git(false)
+        .add_custom_ignore_filename(TC_IGNORE)
+        .filter_entry(|entry| {
+            !entry.file_type().is_some_and(|t| t.is_dir())
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| SKIPPED_DIRS.contains(&name))
+        })
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .build()
+}
+
+/// The files under `dir` that `add_dir` would look at, found as they are
+/// iterated so a caller can stop early.
+pub(crate) fn files_under(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    walk(dir)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+}
+
+/// Reads `path` if it is text. A `whole` file over the size limit is
+/// refused before it is read; for an excerpt the limit applies to the part
+/// taken, so that is checked afterwards.
+pub(crate) fn read_text(path: &Path, whole: bool) -> Result<String, SkipReason> {
+    if is_lockfile(path) {
+        return Err(SkipReason::Lockfile);
+    }
+    if whole {
+        let size = fs::metadata(path)
+            .map_err(|e| SkipReason::Unreadable(e.to_string()))?
+            .len() as usize;
+        if size > MAX_FILE_BYTES {
+            return Err(SkipReason::TooLarge(size));
+        }
+    }
+    let bytes = fs::read(path).map_err(|e| SkipReason::Unreadable(e.to_string()))?;
+    if is_binary(&bytes) {
+        return Err(SkipReason::Binary);
+    }
+    // Text in another encoding is still worth reading.
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Collects the files under `dir` with one of `extensions`, or all of them
+/// when `extensions` is empty.
+pub(crate) fn walk_directory(dir: &Path, extensions: &HashSet<&str>) -> FileSet {
+    let mut set = FileSet::default();
+    set.add_dir(dir, |path| has_extension(path, extensions));
+    set
+}
+
+/// Expands file arguments into a `FileSet`. An argument can be a file, a
+/// glob such as `src/**/*.rs`, a directory followed by the extensions to
+/// take from it (`src/ rs toml`), or part of a file (`src/run.rs:10-80`,
+/// `src/run.rs#as_repl`). Arguments that match nothing are returned
+/// as errors so one bad path does not stop the rest.
+pub(crate) fn expand_file_args(args: &[String]) -> (FileSet, Vec<String>) {
+    let args: Vec<&str> = args
+        .iter()
+        .map(|a| a.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .filter(|a| !a.is_empty())
+        .collect();
+    let mut set = FileSet::default();
+    let mut errors = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i];
+        i += 1;
+        let before = set.files.len() + set.skipped.len();
+
+        if !Path::new(arg).exists()
+            && let Some((path, selection)) = Selection::parse(arg)
+            && Path::new(path).is_file()
+        {
+            set.add_excerpt(Path::new(path), selection);
+        } else if is_glob(arg) {
+            // As in a shell, `*` stays within one directory and `**` crosses them.
+            let options = MatchOptions {
+                require_literal_separator: true,
+                ..Default::default()
+            };
+            match Pattern::new(arg) {
+                Ok(pattern) => set.add_dir(&glob_base(arg), |path| {
+                    pattern.matches_path_with(path.strip_prefix("./").unwrap_or(path), options)
+                }),
+                Err(e) => {
+                    errors.push(format!("{}: {}", arg, e));
+                    continue;
+                }
+            }
+        } else if Path::new(arg).is_dir() {
+            let mut extensions = HashSet::new();
+            while let Some(ext) = args.get(i).filter(|a| is_extension(a)) {
+                extensions.insert(ext.trim_start_matches('.'));
+                i += 1;
+            }
+            set.add_dir(Path::new(arg), |path| has_extension(path, &extensions));
+        } else if Path::new(arg).is_file() {
+            set.add_file(Path::new(arg));
+        } else {
+            errors.push(format!("{}: no such file or directory", arg));
+            continue;
+        }
+
+        if set.files.len() + set.skipped.len() == before {
+            errors.push(format!("{}: no matching files", arg));
+        }
+    }
+    (set, errors)
+}
+
+fn has_extension(path: &Path, extensions: &HashSet<&str>) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(e))
+}
+
+fn is_lockfile(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| LOCKFILES.contains(&name))
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '['])
+}
+
+// The directory part of a glob before its first wildcard.
+fn glob_base(pattern: &str) -> PathBuf {
+    let base: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect();
+    if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    }
+}
+
+// A word like `rs` or `.toml` that is not itself a file.
+fn is_extension(arg: &str) -> bool {
+    let ext = arg.strip_prefix('.').unwrap_or(arg);
+    !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()) && !Path::new(arg).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand_file_args() {
+        let (set, errors) = expand_file_args(&args(&["src/commands", "rs", "Cargo.toml"]));
+        assert!(errors.is_empty());
+        assert!(set.files.iter().any(|(o, _)| o.path.ends_with("gf.rs")));
+        assert!(set.files.iter().any(|(o, _)| o.path == "Cargo.toml"));
+
+        let (set, _) = expand_file_args(&args(&["'src/commands/g*.rs'"]));
+        assert!(!set.files.is_empty());
+        assert!(set.files.iter().all(|(o, _)| o.path.contains("commands/g")));
+
+        let (set, _) = expand_file_args(&args(&["src/*.rs"]));
+        assert!(set.files.iter().any(|(o, _)| o.path == "src/walker.rs"));
+        assert!(
+            !set.files
+                .iter()
+                .any(|(o, _)| o.path == "src/commands/gf.rs")
+        );
+        let (set, _) = expand_file_args(&args(&["src/**/gf.rs"]));
+        assert!(
+            set.files
+                .iter()
+                .any(|(o, _)| o.path == "src/commands/gf.rs")
+        );
+
+        let (set, errors) = expand_file_args(&args(&["no/such/file", "src/*.nope"]));
+        assert!(set.files.is_empty());
+        assert_eq!(errors.len(), 2);
+
+        let (set, errors) = expand_file_args(&args(&["src/walker.rs#read_text", "Cargo.toml:1-2"]));
+        assert!(errors.is_empty());
+        assert_eq!(set.files[0].0.label(), "src/walker.rs#read_text");
+        assert!(
+            set.files[0]
+                .1
+                .starts_with("/// Reads `path` if it is text.")
+        );
+        assert_eq!(set.files[1].1, "[package]\nname = \"tc\"");
+    }
+
+    #[test]
+    fn test_skips_lockfiles_binaries_and_ignored_files() {
+        let (set, _) = expand_file_args(&args(&["Cargo.lock"]));
+        assert_eq!(
+            set.skipped,
+            vec![("Cargo.lock".to_string(), SkipReason::Lockfile)]
+        );
+
+        // `target/` is in .gitignore and skipped regardless.
+        let set = walk_directory(Path::new("."), &HashSet::from(["rs"]));
+        assert!(set.files.iter().any(|(o, _)| o.path.ends_with("walker.rs")));
+        assert!(!set.files.iter().any(|(o, _)| o.path.contains("target/")));
+
+        let big = std::env::temp_dir().join(format!("tc_big_{}.log", std::process::id()));
+        let _ = fs::write(&big, "x".repeat(MAX_FILE_BYTES + 1));
+        assert_eq!(
+            read_text(&big, true),
+            Err(SkipReason::TooLarge(MAX_FILE_BYTES + 1))
+        );
+        assert!(read_text(&big, false).is_ok());
+        let _ = fs::remove_file(&big);
+
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(!is_binary("plain text, ünïcode".as_bytes()));
+        assert_eq!(glob_base("src/**/*.rs"), PathBuf::from("src"));
+        assert_eq!(glob_base("*.md"), PathBuf::from("."));
+    }
+}
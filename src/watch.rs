@@ -0,0 +1,180 @@
+use crate::conversation::{ConversationContext, Message};
+use crate::excerpt::Selection;
+use crate::tc_config::WatchMode;
+use crate::walker::{FileSet, SkipReason};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// The file on disk a context message was read from, the part of it that
+/// was taken, and a hash of the content the message holds, so changes can
+/// be noticed later.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FileOrigin {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<Selection>,
+    pub hash: String,
+}
+
+impl FileOrigin {
+    pub fn new(path: &str, selection: Option<Selection>, content: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            selection,
+            hash: content_hash(content),
+        }
+    }
+
+    /// `src/run.rs`, `src/run.rs:10-80` or `src/run.rs#as_repl`.
+    pub fn label(&self) -> String {
+        match &self.selection {
+            Some(selection) => format!("{}{}", self.path, selection),
+            None => self.path.clone(),
+        }
+    }
+
+    /// The selected content as it is on disk now, read with the same
+    /// checks as when it was added. `Unreadable` means the file or the
+    /// selected part of it is gone.
+    fn read(&self) -> Result<String, SkipReason> {
+        let mut set = FileSet::default();
+        match &self.selection {
+            Some(selection) => set.add_excerpt(Path::new(&self.path), selection.clone()),
+            None => set.add_file(Path::new(&self.path)),
+        }
+        match (set.files.pop(), set.skipped.pop()) {
+            (Some((_, content)), _) => Ok(content),
+            (None, Some((_, reason))) => Err(reason),
+            (None, None) => Err(SkipReason::Unreadable(self.path.clone())),
+        }
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn changed_note(path: &str) -> String {
+    format!(
+        "[{} has changed on disk since this copy was added]\n\n",
+        path
+    )
+}
+
+fn removed_note(path: &str) -> String {
+    format!("[{} has been removed since this copy was added]\n\n", path)
+}
+
+/// Brings file messages in `ctx` up to date with the disk before a send.
+/// `Replace` swaps in the new content; `Annotate` keeps the old copy and
+/// marks it as out of date, as does `Replace` when the new content would
+/// not be added (binary, too large). Returns one line per message that
+/// changed.
+pub(crate) fn refresh_files(ctx: &mut ConversationContext, mode: WatchMode) -> Vec<String> {
+    if mode == WatchMode::Off {
+        return vec![];
+    }
+    ctx.input
+        .iter_mut()
+        .filter_map(|message| refresh_message(message, mode))
+        .collect()
+}
+
+fn refresh_message(message: &mut Message, mode: WatchMode) -> Option<String> {
+    let origin = message.file.as_mut()?;
+    let path = origin.label();
+    let current = origin.read();
+    let current_hash = current.as_deref().ok().map(content_hash);
+
+    if mode == WatchMode::Replace
+        && let Ok(content) = &current
+    {
+        if current_hash.as_ref() == Some(&origin.hash) {
+            return None;
+        }
+        origin.hash = current_hash.unwrap_or_default();
+        message.content = Message::from_file(&path, content, message.source).content;
+        return Some(format!("Refreshed {}", path));
+    }
+
+    let note = match (&current, current_hash) {
+        (Err(SkipReason::Unreadable(_)), _) => removed_note(&path),
+        (Err(_), _) => changed_note(&path),
+        (Ok(_), Some(hash)) if hash == origin.hash => String::new(),
+        (Ok(_), _) => changed_note(&path),
+    };
+    // Drop a note that no longer applies, then add the current one once.
+    let body = message
+        .content
+        .strip_prefix(&changed_note(&path))
+        .or_else(|| message.content.strip_prefix(&removed_note(&path)))
+        .unwrap_or(&message.content)
+        .to_string();
+    let content = format!("{}{}", note, body);
+    if content == message.content {
+        return None;
+    }
+    message.content = content;
+    if note.is_empty() {
+        return Some(format!("{} is back in sync", path));
+    }
+    let mut report = note.trim().trim_matches(['[', ']']).to_string();
+    if mode == WatchMode::Replace
+        && let Err(reason) = current
+        && !matches!(reason, SkipReason::Unreadable(_))
+    {
+        report.push_str(&format!(", not refreshed ({})", reason));
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::MessageSource;
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+
+    #[test]
+    fn test_refresh_files() -> Result<(), Box<dyn Error>> {
+        let path = env::temp_dir().join(format!("tc_watch_{}.rs", std::process::id()));
+        let path_str = path.display().to_string();
+        fs::write(&path, "fn old() {}")?;
+
+        let mut ctx = ConversationContext::new("gpt-4o", true);
+        ctx.input.push(Message::from_disk(
+            FileOrigin::new(&path_str, None, "fn old() {}"),
+            "fn old() {}",
+            MessageSource::Gf,
+        ));
+        assert!(refresh_files(&mut ctx, WatchMode::Annotate).is_empty());
+
+        fs::write(&path, "fn new() {}")?;
+        assert!(refresh_files(&mut ctx, WatchMode::Off).is_empty());
+        assert_eq!(refresh_files(&mut ctx, WatchMode::Annotate).len(), 1);
+        assert!(ctx.input[0].content.starts_with(&changed_note(&path_str)));
+        // Only reported once.
+        assert!(refresh_files(&mut ctx, WatchMode::Annotate).is_empty());
+
+        assert_eq!(refresh_files(&mut ctx, WatchMode::Replace).len(), 1);
+        assert!(ctx.input[0].content.ends_with("fn new() {}"));
+        assert!(!ctx.input[0].content.starts_with('['));
+
+        // Content that would not be added is flagged, not sent.
+        fs::write(&path, "fn new() {}\0")?;
+        let report = refresh_files(&mut ctx, WatchMode::Replace);
+        assert!(report[0].ends_with("not refreshed (binary)"));
+        assert!(ctx.input[0].content.starts_with(&changed_note(&path_str)));
+        assert!(ctx.input[0].content.ends_with("fn new() {}"));
+
+        fs::remove_file(&path)?;
+        assert_eq!(refresh_files(&mut ctx, WatchMode::Replace).len(), 1);
+        assert!(ctx.input[0].content.starts_with(&removed_note(&path_str)));
+        Ok(())
+    }
+}